@@ -11,14 +11,33 @@ use dove::cmd::init::Init;
 use dove::cmd::new::New;
 use dove::cmd::metadata::Metadata;
 use dove::cmd::fetch::Fetch;
+use dove::cmd::update::Update;
 use dove::cmd::build::Build;
 use dove::cmd::test::Test;
 use dove::cmd::run::Run;
 use dove::cmd::ct::CreateTransactionCmd;
+use dove::cmd::pac::Pac;
+use dove::cmd::tree::Tree;
+use dove::cmd::doc::Doc;
+use dove::cmd::fmt::Fmt;
+use dove::cmd::lint::Lint;
+use dove::cmd::check::Check;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "Dove", version = git_hash::crate_version_with_git_hash_short!())]
-enum Opt {
+struct Opt {
+    #[structopt(
+        help = "Build profile from the manifest.",
+        long = "profile",
+        global = true
+    )]
+    profile: Option<String>,
+    #[structopt(subcommand)]
+    cmd: Command,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
     #[structopt(about = "Init directory as move project")]
     Init {
         #[structopt(flatten)]
@@ -39,6 +58,11 @@ enum Opt {
         #[structopt(flatten)]
         cmd: Fetch,
     },
+    #[structopt(about = "Update git dependencies")]
+    Update {
+        #[structopt(flatten)]
+        cmd: Update,
+    },
     #[structopt(about = "Build project")]
     Build {
         #[structopt(flatten)]
@@ -64,22 +88,60 @@ enum Opt {
         #[structopt(flatten)]
         cmd: CreateTransactionCmd,
     },
+    #[structopt(about = "Inspect module package")]
+    Pac {
+        #[structopt(flatten)]
+        cmd: Pac,
+    },
+    #[structopt(about = "Print module dependency graph")]
+    Tree {
+        #[structopt(flatten)]
+        cmd: Tree,
+    },
+    #[structopt(about = "Generate modules documentation")]
+    Doc {
+        #[structopt(flatten)]
+        cmd: Doc,
+    },
+    #[structopt(about = "Format move sources")]
+    Fmt {
+        #[structopt(flatten)]
+        cmd: Fmt,
+    },
+    #[structopt(about = "Run linter")]
+    Lint {
+        #[structopt(flatten)]
+        cmd: Lint,
+    },
+    #[structopt(about = "Type-check project sources without building")]
+    Check {
+        #[structopt(flatten)]
+        cmd: Check,
+    },
 }
 
 fn main() {
-    let matches = Opt::from_args();
+    let Opt { profile, cmd } = Opt::from_args();
+    let profile = profile.as_deref();
 
     let _pool = ConstPool::new();
-    handle_error(match matches {
-        Opt::Clean { cmd } => cmd.execute(),
-        Opt::New { cmd } => cmd.execute(),
-        Opt::Init { cmd } => cmd.execute(),
-        Opt::Metadata { cmd } => cmd.execute(),
-        Opt::Fetch { cmd } => cmd.execute(),
-        Opt::Build { cmd } => cmd.execute(),
-        Opt::Test { cmd } => cmd.execute(),
-        Opt::Run { cmd } => cmd.execute(),
-        Opt::Ct { cmd } => cmd.execute(),
+    handle_error(match cmd {
+        Command::Clean { cmd } => cmd.execute(profile),
+        Command::New { cmd } => cmd.execute(profile),
+        Command::Init { cmd } => cmd.execute(profile),
+        Command::Metadata { cmd } => cmd.execute(profile),
+        Command::Fetch { cmd } => cmd.execute(profile),
+        Command::Update { cmd } => cmd.execute(profile),
+        Command::Build { cmd } => cmd.execute(profile),
+        Command::Test { cmd } => cmd.execute(profile),
+        Command::Run { cmd } => cmd.execute(profile),
+        Command::Ct { cmd } => cmd.execute(profile),
+        Command::Pac { cmd } => cmd.execute(profile),
+        Command::Tree { cmd } => cmd.execute(profile),
+        Command::Doc { cmd } => cmd.execute(profile),
+        Command::Fmt { cmd } => cmd.execute(profile),
+        Command::Lint { cmd } => cmd.execute(profile),
+        Command::Check { cmd } => cmd.execute(profile),
     });
 }
 
@@ -0,0 +1,214 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Sha3};
+
+use crate::context::Context;
+
+/// Build cache directory name.
+pub const CACHE_DIR: &str = ".build_cache";
+/// Build cache index file name.
+const CACHE_FILE: &str = "cache.toml";
+
+/// Compiled units cache.
+/// Maps source files to the units compiled from them.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildCache {
+    /// Fingerprint of the build settings and dependencies.
+    pub key: String,
+    /// Cached source files by path.
+    #[serde(default)]
+    pub files: BTreeMap<String, CachedFile>,
+}
+
+/// Cached source file.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CachedFile {
+    /// Hash of the source text.
+    pub hash: String,
+    /// Units compiled from the file.
+    #[serde(default)]
+    pub units: Vec<CachedUnit>,
+}
+
+/// Cached compiled unit.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedUnit {
+    /// Unit name.
+    pub name: String,
+    /// `true` if the unit is a script.
+    pub script: bool,
+    /// Hash of the bytecode.
+    pub hash: String,
+}
+
+impl BuildCache {
+    /// Loads the build cache of the project.
+    /// Returns an empty cache if the cache is missing, unreadable or built with another key.
+    pub fn load(ctx: &Context, key: &str) -> BuildCache {
+        let cache = fs::read_to_string(cache_dir(ctx).join(CACHE_FILE))
+            .ok()
+            .and_then(|cache| toml::from_str::<BuildCache>(&cache).ok());
+        match cache {
+            Some(cache) if cache.key == key => cache,
+            _ => BuildCache {
+                key: key.to_owned(),
+                files: Default::default(),
+            },
+        }
+    }
+
+    /// Returns `true` if the file is cached with the given hash and all its units are present.
+    pub fn is_fresh(&self, ctx: &Context, path: &str, hash: &str) -> bool {
+        let dir = cache_dir(ctx);
+        self.files
+            .get(path)
+            .map(|file| {
+                file.hash == hash
+                    && file
+                        .units
+                        .iter()
+                        .all(|unit| dir.join(unit_file(&unit.hash)).exists())
+            })
+            .unwrap_or(false)
+    }
+
+    /// Returns bytecode of the cached unit.
+    pub fn read_unit(&self, ctx: &Context, unit: &CachedUnit) -> Result<Vec<u8>, Error> {
+        Ok(fs::read(cache_dir(ctx).join(unit_file(&unit.hash)))?)
+    }
+
+    /// Stores the bytecode in the cache and returns its hash.
+    pub fn write_unit(&self, ctx: &Context, bytecode: &[u8]) -> Result<String, Error> {
+        let dir = cache_dir(ctx);
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+        }
+
+        let hash = sha3(bytecode);
+        let path = dir.join(unit_file(&hash));
+        if !path.exists() {
+            fs::write(path, bytecode)?;
+        }
+        Ok(hash)
+    }
+
+    /// Stores the cache and removes units that are no longer referenced.
+    pub fn store(&self, ctx: &Context) -> Result<(), Error> {
+        let dir = cache_dir(ctx);
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+        }
+
+        let used = self
+            .files
+            .values()
+            .flat_map(|file| file.units.iter())
+            .map(|unit| unit_file(&unit.hash))
+            .collect::<HashSet<_>>();
+        for entry in dir.read_dir()? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.ends_with(".mv") && !used.contains(name.as_ref()) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        fs::write(dir.join(CACHE_FILE), toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Incremental SHA3 fingerprint.
+pub struct Fingerprint {
+    digest: Sha3,
+}
+
+impl Fingerprint {
+    /// Creates a new fingerprint.
+    pub fn new() -> Fingerprint {
+        Fingerprint {
+            digest: Sha3::v256(),
+        }
+    }
+
+    /// Adds the bytes to the fingerprint.
+    /// Every chunk is prefixed with its length, so chunk boundaries are significant.
+    pub fn bytes(mut self, bytes: &[u8]) -> Fingerprint {
+        self.digest.update(&(bytes.len() as u64).to_le_bytes());
+        self.digest.update(bytes);
+        self
+    }
+
+    /// Adds the string to the fingerprint.
+    pub fn str(self, value: &str) -> Fingerprint {
+        self.bytes(value.as_bytes())
+    }
+
+    /// Returns hex encoded fingerprint.
+    pub fn finish(self) -> String {
+        let mut output = [0; 32];
+        self.digest.finalize(&mut output);
+        hex::encode(&output)
+    }
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Fingerprint::new()
+    }
+}
+
+/// Returns hex encoded SHA3-256 hash of the bytes.
+pub fn sha3(bytes: &[u8]) -> String {
+    let mut digest = Sha3::v256();
+    digest.update(bytes);
+    let mut output = [0; 32];
+    digest.finalize(&mut output);
+    hex::encode(&output)
+}
+
+fn cache_dir(ctx: &Context) -> PathBuf {
+    ctx.path_for(&ctx.manifest.layout.target).join(CACHE_DIR)
+}
+
+fn unit_file(hash: &str) -> String {
+    format!("{}.mv", hash)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::{BuildCache, CachedFile, CachedUnit, Fingerprint};
+
+    #[test]
+    fn test_fingerprint() {
+        let hash = Fingerprint::new().str("ab").str("c").finish();
+        assert_eq!(hash, Fingerprint::new().str("ab").str("c").finish());
+        assert_ne!(hash, Fingerprint::new().str("a").str("bc").finish());
+        assert_eq!(hash.len(), 64);
+    }
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let mut cache = BuildCache::default();
+        cache.key = Fingerprint::new().str("dfinance").finish();
+        cache.files.insert(
+            "modules/coins.move".to_owned(),
+            CachedFile {
+                hash: Fingerprint::new().str("module Coins {}").finish(),
+                units: vec![CachedUnit {
+                    name: "Coins".to_owned(),
+                    script: false,
+                    hash: Fingerprint::new().bytes(&[0xa1, 0x1c]).finish(),
+                }],
+            },
+        );
+
+        let encoded = toml::to_string(&cache).unwrap();
+        assert_eq!(cache, toml::from_str::<BuildCache>(&encoded).unwrap());
+    }
+}
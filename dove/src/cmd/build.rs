@@ -1,25 +1,31 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Error;
 use diem::{
-    move_lang::{
-        compiled_unit,
-        errors::{FilesSourceText, output_errors},
-    },
-    prelude::CompiledUnit,
+    move_lang::{compiled_unit, errors::output_errors},
+    prelude::{CompiledModule, CompiledUnit, ModuleId},
+    vm::access::ModuleAccess,
 };
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 use termcolor::{ColorChoice, StandardStream};
 
 use lang::builder::{Artifacts, MoveBuilder};
-use lang::compiler::file::load_move_files_with_filter;
+use lang::compiler::address::{NamedAddresses, ProvidedAccountAddress};
+use lang::compiler::file::{load_move_files_with_filter, MoveFile};
 
+use crate::cache::{BuildCache, CachedFile, CachedUnit, Fingerprint, sha3};
 use crate::cmd::{Cmd, load_dependencies};
 use crate::context::Context;
+use crate::index::Index;
+use crate::index::meta::{
+    extract_bytecode_dependencies, extract_script_dependencies, module_id, source_meta, FileMeta,
+};
+use crate::watch::watch;
 
 /// Build dependencies.
 #[derive(StructOpt, Debug)]
@@ -53,18 +59,52 @@ pub struct Build {
         long = "unordered"
     )]
     unordered: bool,
+    #[structopt(
+        help = "Rebuild the project on changes of the source files.",
+        short = "w",
+        long = "watch"
+    )]
+    watch: bool,
 }
 
 impl Cmd for Build {
     fn apply(self, ctx: Context) -> Result<(), Error> {
+        let projects = ctx.projects()?;
+        let mut indexes = projects
+            .iter()
+            .map(|ctx| ctx.build_index())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if self.watch {
+            watch(&mut indexes, |indexes| self.build_all(indexes))
+        } else {
+            self.build_all(&mut indexes)
+        }
+    }
+}
+
+impl Build {
+    /// Build all projects.
+    fn build_all(&self, indexes: &mut [Index]) -> Result<(), Error> {
+        for index in indexes {
+            if index.ctx.workspace_dir.is_some() {
+                println!("Build:[{}]", index.ctx.project_name());
+            }
+            self.build(index)?;
+        }
+        Ok(())
+    }
+
+    /// Build project.
+    fn build(&self, index: &mut Index) -> Result<(), Error> {
+        let ctx = index.ctx;
         let dirs = ctx.paths_for(&[
             &ctx.manifest.layout.script_dir,
             &ctx.manifest.layout.module_dir,
         ]);
 
-        let mut index = ctx.build_index()?;
-
         let dep_set = index.make_dependency_set(&dirs)?;
+        let dep_senders = index.dep_senders(&dep_set)?;
         let mut dep_list = load_dependencies(dep_set)?;
 
         let (exclude_files, exclude_modules): (Vec<_>, Vec<_>) =
@@ -90,137 +130,559 @@ impl Cmd for Build {
         }
 
         let sender = ctx.account_address()?;
-        let Artifacts { files, prog } =
-            MoveBuilder::new(ctx.dialect.as_ref(), Some(sender).as_ref())
-                .build(&source_list, &dep_list);
+        let addresses = ctx.named_addresses()?;
+
+        let key = self.cache_key(ctx, &sender, &addresses, &dep_senders, &dep_list);
+        let mut cache = BuildCache::load(ctx, &key);
+
+        let hashes = source_list
+            .iter()
+            .map(|file| Fingerprint::new().str(file.content()).finish())
+            .collect::<Vec<_>>();
+        let metas = source_list
+            .iter()
+            .map(|file| {
+                let file_sender = dep_senders.get(file.name()).unwrap_or(&sender);
+                source_meta(
+                    Path::new(file.name()),
+                    Some(file_sender.as_account_address()),
+                    ctx.dialect.as_ref(),
+                    &addresses,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let dirty = dirty_files(ctx, &cache, &source_list, &hashes, &metas);
+
+        let mut ice_errors = None;
+        let mut extra_units = vec![];
+        if dirty.iter().any(|dirty| *dirty) {
+            let mut targets = vec![];
+            for (file, dirty) in source_list.iter().zip(&dirty) {
+                if *dirty {
+                    targets.push(file.clone());
+                } else if cache
+                    .files
+                    .get(file.name())
+                    .map(|cached| cached.units.iter().any(|unit| !unit.script))
+                    .unwrap_or(false)
+                {
+                    // Unchanged modules are only type-checked as dependencies.
+                    dep_list.push(file.clone());
+                }
+            }
+
+            let Artifacts { files, prog } =
+                MoveBuilder::new(ctx.dialect.as_ref(), Some(sender).as_ref())
+                    .with_addresses(addresses)
+                    .with_dep_senders(dep_senders)
+                    .build(&targets, &dep_list);
+
+            let compiled_units = match prog {
+                Err(errors) => {
+                    let mut writer = StandardStream::stderr(ColorChoice::Auto);
+                    output_errors(&mut writer, files, errors);
+                    return Err(anyhow!("could not compile:{}", ctx.project_name()));
+                }
+                Ok(compiled_units) => compiled_units,
+            };
+            let (compiled_units, errors) = compiled_unit::verify_units(compiled_units);
+            if !errors.is_empty() {
+                ice_errors = Some((files, errors));
+            }
+
+            let module_files = source_list
+                .iter()
+                .zip(&metas)
+                .zip(&dirty)
+                .filter(|(_, dirty)| **dirty)
+                .flat_map(|((file, meta), _)| {
+                    meta.meta
+                        .iter()
+                        .map(move |def| (def.module_id.clone(), file.name()))
+                })
+                .collect::<HashMap<_, _>>();
+            let mut file_units = source_list
+                .iter()
+                .zip(&dirty)
+                .filter(|(_, dirty)| **dirty)
+                .map(|(file, _)| (file.name(), vec![]))
+                .collect::<HashMap<_, _>>();
+
+            for unit in compiled_units {
+                let (file, script) = match &unit {
+                    CompiledUnit::Module { module, .. } => {
+                        (module_files.get(&module.self_id()).copied(), false)
+                    }
+                    CompiledUnit::Script { loc, .. } => (Some(loc.file()), true),
+                };
+                let cached = CachedUnit {
+                    name: unit.name(),
+                    script,
+                    hash: cache.write_unit(ctx, &unit.serialize())?,
+                };
+                match file.and_then(|file| file_units.get_mut(file)) {
+                    Some(units) => units.push(cached),
+                    None => extra_units.push(cached),
+                }
+            }
+
+            for (file, hash) in source_list.iter().zip(&hashes) {
+                if let Some(units) = file_units.remove(file.name()) {
+                    cache.files.insert(
+                        file.name().to_owned(),
+                        CachedFile {
+                            hash: hash.to_owned(),
+                            units,
+                        },
+                    );
+                }
+            }
+        }
+
+        let sources = source_list
+            .iter()
+            .map(|file| file.name())
+            .collect::<HashSet<_>>();
+        cache
+            .files
+            .retain(|path, _| sources.contains(path.as_str()));
+
+        let mut modules = vec![];
+        let mut scripts = vec![];
+        let cached_units = source_list
+            .iter()
+            .filter_map(|file| cache.files.get(file.name()))
+            .flat_map(|cached| cached.units.iter())
+            .chain(extra_units.iter());
+        for unit in cached_units {
+            if exclude_modules.contains(&&unit.name) {
+                continue;
+            }
+
+            let built = BuiltUnit {
+                name: unit.name.clone(),
+                bytecode: cache.read_unit(ctx, unit)?,
+            };
+            if unit.script {
+                scripts.push(built);
+            } else {
+                modules.push(built);
+            }
+        }
 
-        match prog {
-            Err(errors) => {
+        let modules = publish_order(modules)?;
+        let module_paths = self.store_modules(ctx, &modules)?;
+        let script_paths = self.store_scripts(ctx, &scripts)?;
+        store_build_info(ctx, &modules, &module_paths, &scripts, &script_paths)?;
+
+        if ice_errors.is_some() || !extra_units.is_empty() {
+            // Units that failed verification or have no known source file can't be reused.
+            cache.files.clear();
+        }
+        cache.store(ctx)?;
+
+        match ice_errors {
+            Some((files, errors)) => {
                 let mut writer = StandardStream::stderr(ColorChoice::Auto);
                 output_errors(&mut writer, files, errors);
-                Err(anyhow!("could not compile:{}", ctx.project_name()))
-            }
-            Ok(compiled_units) => {
-                self.verify_and_store(&ctx, files, compiled_units, &exclude_modules)
+                Err(anyhow!("could not verify:{}", ctx.project_name()))
             }
+            None => Ok(()),
         }
     }
-}
 
-impl Build {
-    /// Verify and store compilation results.
-    fn verify_and_store(
+    /// Returns fingerprint of the build settings and dependencies.
+    fn cache_key(
         &self,
         ctx: &Context,
-        files: FilesSourceText,
-        compiled_units: Vec<CompiledUnit>,
-        exclude_modules: &[&String],
-    ) -> Result<(), Error> {
-        let (compiled_units, ice_errors) = compiled_unit::verify_units(compiled_units);
-        let (modules, scripts): (Vec<_>, Vec<_>) = compiled_units
-            .into_iter()
-            .filter(|u| !exclude_modules.contains(&&u.name()))
-            .partition(|u| matches!(u, CompiledUnit::Module { .. }));
-
-        self.store_modules(ctx, modules)?;
-        self.store_scripts(ctx, scripts)?;
-
-        if !ice_errors.is_empty() {
-            let mut writer = StandardStream::stderr(ColorChoice::Auto);
-            output_errors(&mut writer, files, ice_errors);
-            Err(anyhow!("could not verify:{}", ctx.project_name()))
-        } else {
-            Ok(())
+        sender: &ProvidedAccountAddress,
+        addresses: &NamedAddresses,
+        dep_senders: &HashMap<String, ProvidedAccountAddress>,
+        deps: &[MoveFile],
+    ) -> String {
+        let mut fingerprint = Fingerprint::new()
+            .str(env!("CARGO_PKG_VERSION"))
+            .str(ctx.dialect.name())
+            .str(&sender.lowered())
+            .str(if self.tree { "tree" } else { "" });
+        for (name, address) in addresses.iter() {
+            fingerprint = fingerprint.str(name).str(&address.lowered());
         }
+        let mut dep_senders = dep_senders.iter().collect::<Vec<_>>();
+        dep_senders.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        for (path, address) in dep_senders {
+            fingerprint = fingerprint.str(path).str(&address.lowered());
+        }
+
+        let mut deps = deps
+            .iter()
+            .map(|dep| (dep.name(), dep.content()))
+            .collect::<Vec<_>>();
+        deps.sort_unstable();
+        for (name, content) in deps {
+            fingerprint = fingerprint.str(name).str(content);
+        }
+        fingerprint.finish()
     }
 
-    fn store_modules(&self, ctx: &Context, units: Vec<CompiledUnit>) -> Result<(), Error> {
-        if !units.is_empty() {
-            if self.package {
-                let packages_dir = ctx.path_for(&ctx.manifest.layout.packages_output);
-                if !packages_dir.exists() {
-                    fs::create_dir_all(&packages_dir)?;
-                }
+    /// Stores modules and returns their output paths.
+    fn store_modules(&self, ctx: &Context, units: &[BuiltUnit]) -> Result<Vec<PathBuf>, Error> {
+        if units.is_empty() {
+            return Ok(vec![]);
+        }
 
-                let pac_file = match &self.output {
-                    None => {
-                        let mut pac_file = match &ctx.manifest.package.name {
-                            None => packages_dir.join("modules"),
-                            Some(pac_name) => packages_dir.join(pac_name),
-                        };
-                        pac_file.set_extension("pac");
-                        pac_file
-                    }
-                    Some(name) => {
-                        let mut pac_file = packages_dir.join(name);
-                        if !name.to_lowercase().ends_with(".pac") {
-                            pac_file.set_extension("pac");
-                        }
-                        pac_file
-                    }
-                };
+        if self.package {
+            let packages_dir = ctx.path_for(&ctx.manifest.layout.packages_output);
+            if !packages_dir.exists() {
+                fs::create_dir_all(&packages_dir)?;
+            }
 
-                println!("Package content: ");
-                for unit in &units {
-                    println!("\t{}", unit.name());
+            let pac_file = match &self.output {
+                None => {
+                    let mut pac_file = match &ctx.manifest.package.name {
+                        None => packages_dir.join("modules"),
+                        Some(pac_name) => packages_dir.join(pac_name),
+                    };
+                    pac_file.set_extension("pac");
+                    pac_file
                 }
-                println!("Store: {:?}", pac_file.as_os_str());
-                let package = ModulePackage::with_units(units);
-                File::create(&pac_file)?.write_all(&package.encode()?)?
-            } else {
-                let modules_dir = ctx.path_for(&ctx.manifest.layout.module_output);
-                if modules_dir.exists() {
-                    fs::remove_dir_all(&modules_dir)?;
+                Some(name) => {
+                    let mut pac_file = packages_dir.join(name);
+                    if !name.to_lowercase().ends_with(".pac") {
+                        pac_file.set_extension("pac");
+                    }
+                    pac_file
                 }
-                fs::create_dir_all(&modules_dir)?;
+            };
 
-                self.store_units(units, &modules_dir)?;
+            println!("Package content: ");
+            for unit in units {
+                println!("\t{}", unit.name);
             }
+            println!("Store: {:?}", pac_file.as_os_str());
+            let package = ModulePackage::with_units(units);
+            File::create(&pac_file)?.write_all(&package.encode()?)?;
+            Ok(vec![pac_file; units.len()])
+        } else {
+            let modules_dir = ctx.path_for(&ctx.manifest.layout.module_output);
+            self.store_units(units, &modules_dir)
         }
-        Ok(())
     }
 
-    fn store_scripts(&self, ctx: &Context, units: Vec<CompiledUnit>) -> Result<(), Error> {
-        if !units.is_empty() {
-            let scripts_dir = ctx.path_for(&ctx.manifest.layout.script_output);
-            if scripts_dir.exists() {
-                fs::remove_dir_all(&scripts_dir)?;
-            }
-            fs::create_dir_all(&scripts_dir)?;
-
-            self.store_units(units, &scripts_dir)?;
+    /// Stores scripts and returns their output paths.
+    fn store_scripts(&self, ctx: &Context, units: &[BuiltUnit]) -> Result<Vec<PathBuf>, Error> {
+        if units.is_empty() {
+            return Ok(vec![]);
         }
-        Ok(())
+
+        let scripts_dir = ctx.path_for(&ctx.manifest.layout.script_output);
+        self.store_units(units, &scripts_dir)
     }
 
-    fn store_units(&self, units: Vec<CompiledUnit>, base_dir: &Path) -> Result<(), Error> {
-        for (idx, unit) in units.into_iter().enumerate() {
+    /// Writes units to the directory.
+    /// Unchanged files are kept and stale units of the previous builds are removed.
+    fn store_units(&self, units: &[BuiltUnit], base_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        if !base_dir.exists() {
+            fs::create_dir_all(&base_dir)?;
+        }
+
+        let mut paths = Vec::with_capacity(units.len());
+        for (idx, unit) in units.iter().enumerate() {
             let mut path = if !self.unordered {
-                base_dir.join(format!("{}_{}", idx, unit.name()))
+                base_dir.join(format!("{}_{}", idx, unit.name))
             } else {
-                base_dir.join(unit.name())
+                base_dir.join(&unit.name)
             };
 
             path.set_extension("mv");
-            File::create(&path)?.write_all(&unit.serialize())?
+            let is_changed = fs::read(&path)
+                .map(|bytecode| bytecode != unit.bytecode)
+                .unwrap_or(true);
+            if is_changed {
+                File::create(&path)?.write_all(&unit.bytecode)?
+            }
+            paths.push(path);
         }
-        Ok(())
+
+        for entry in base_dir.read_dir()? {
+            let path = entry?.path();
+            if path.is_file() && !paths.contains(&path) {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(paths)
+    }
+}
+
+/// Compiled unit ready to be stored.
+struct BuiltUnit {
+    name: String,
+    bytecode: Vec<u8>,
+}
+
+/// Returns `true` for every source file that has to be recompiled.
+/// A file is rebuilt if its text has changed or if it uses a module from a rebuilt file.
+fn dirty_files(
+    ctx: &Context,
+    cache: &BuildCache,
+    source_list: &[MoveFile],
+    hashes: &[String],
+    metas: &[FileMeta],
+) -> Vec<bool> {
+    let mut dirty = source_list
+        .iter()
+        .zip(hashes)
+        .map(|(file, hash)| !cache.is_fresh(ctx, file.name(), hash))
+        .collect::<Vec<_>>();
+
+    loop {
+        let changed = metas
+            .iter()
+            .zip(&dirty)
+            .filter(|(_, dirty)| **dirty)
+            .flat_map(|(meta, _)| meta.meta.iter().map(|def| def.module_id.clone()))
+            .collect::<HashSet<_>>();
+
+        let mut is_updated = false;
+        for (idx, meta) in metas.iter().enumerate() {
+            let uses_changed = meta
+                .meta
+                .iter()
+                .any(|def| def.imports.iter().any(|import| changed.contains(&**import)));
+            if !dirty[idx] && uses_changed {
+                dirty[idx] = true;
+                is_updated = true;
+            }
+        }
+
+        if !is_updated {
+            return dirty;
+        }
+    }
+}
+
+/// Orders modules so that every module follows its dependencies.
+fn publish_order(modules: Vec<BuiltUnit>) -> Result<Vec<BuiltUnit>, Error> {
+    let ids = modules
+        .iter()
+        .map(|unit| {
+            CompiledModule::deserialize(&unit.bytecode)
+                .map(|module| module.self_id())
+                .map_err(|err| anyhow!("Failed to deserialize module {}: {:?}", unit.name, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let deps = modules
+        .iter()
+        .map(|unit| extract_bytecode_dependencies(&unit.bytecode))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut done = vec![false; modules.len()];
+    let mut order = Vec::with_capacity(modules.len());
+    while order.len() < modules.len() {
+        let is_ready = |idx: usize| {
+            deps[idx]
+                .iter()
+                .all(|dep| ids.iter().zip(&done).all(|(id, done)| *done || id != dep))
+        };
+        let next = (0..modules.len())
+            .find(|idx| !done[*idx] && is_ready(*idx))
+            .or_else(|| (0..modules.len()).find(|idx| !done[*idx]))
+            .ok_or_else(|| anyhow!("Failed to order modules"))?;
+        done[next] = true;
+        order.push(next);
     }
+
+    let mut modules = modules.into_iter().map(Some).collect::<Vec<_>>();
+    Ok(order
+        .into_iter()
+        .filter_map(|idx| modules[idx].take())
+        .collect())
+}
+
+/// Build artifacts file name.
+const BUILD_INFO: &str = "build-info.json";
+
+/// Description of the build artifacts.
+#[derive(Serialize, Deserialize, Debug)]
+struct BuildInfo {
+    units: Vec<UnitInfo>,
+}
+
+/// Compiled unit description.
+#[derive(Serialize, Deserialize, Debug)]
+struct UnitInfo {
+    /// `module` or `script`.
+    kind: String,
+    /// Fully qualified module id or script name.
+    id: String,
+    /// Path to the output file.
+    path: PathBuf,
+    /// SHA3-256 hash of the bytecode.
+    hash: String,
+    /// Publish order index.
+    order: usize,
+    /// Fully qualified ids of the imported modules.
+    imports: Vec<String>,
+}
+
+/// Writes description of the stored units to the target directory.
+fn store_build_info(
+    ctx: &Context,
+    modules: &[BuiltUnit],
+    module_paths: &[PathBuf],
+    scripts: &[BuiltUnit],
+    script_paths: &[PathBuf],
+) -> Result<(), Error> {
+    let mut units = Vec::with_capacity(modules.len() + scripts.len());
+    for (order, (unit, path)) in modules.iter().zip(module_paths).enumerate() {
+        let module = CompiledModule::deserialize(&unit.bytecode)
+            .map_err(|err| anyhow!("Failed to deserialize module {}: {:?}", unit.name, err))?;
+        units.push(UnitInfo {
+            kind: "module".to_owned(),
+            id: module_id(&module.self_id()),
+            path: path.to_owned(),
+            hash: sha3(&unit.bytecode),
+            order,
+            imports: sorted_ids(extract_bytecode_dependencies(&unit.bytecode)?),
+        });
+    }
+    for (order, (unit, path)) in scripts.iter().zip(script_paths).enumerate() {
+        units.push(UnitInfo {
+            kind: "script".to_owned(),
+            id: unit.name.clone(),
+            path: path.to_owned(),
+            hash: sha3(&unit.bytecode),
+            order,
+            imports: sorted_ids(extract_script_dependencies(&unit.bytecode)?),
+        });
+    }
+
+    let target = ctx.path_for(&ctx.manifest.layout.target);
+    if !target.exists() {
+        fs::create_dir_all(&target)?;
+    }
+    let info = serde_json::to_string_pretty(&BuildInfo { units })?;
+    fs::write(target.join(BUILD_INFO), info)?;
+    Ok(())
+}
+
+fn sorted_ids(ids: HashSet<ModuleId>) -> Vec<String> {
+    let mut ids = ids.iter().map(module_id).collect::<Vec<_>>();
+    ids.sort();
+    ids
 }
 
+/// Package of modules stored by `dove build --package`.
 #[derive(Serialize, Deserialize, Debug)]
-struct ModulePackage {
-    modules: Vec<Vec<u8>>,
+pub struct ModulePackage {
+    /// Module bytecodes in publish order.
+    pub modules: Vec<Vec<u8>>,
 }
 
 impl ModulePackage {
-    fn with_units(units: Vec<CompiledUnit>) -> ModulePackage {
+    fn with_units(units: &[BuiltUnit]) -> ModulePackage {
         ModulePackage {
-            modules: units.into_iter().map(|unit| unit.serialize()).collect(),
+            modules: units.iter().map(|unit| unit.bytecode.clone()).collect(),
         }
     }
 
+    /// Encodes the package with BCS.
     pub fn encode(&self) -> Result<Vec<u8>, Error> {
         diem::bcs::to_bytes(&self).map_err(|err| err.into())
     }
+
+    /// Decodes the BCS encoded package.
+    pub fn decode(bytes: &[u8]) -> Result<ModulePackage, Error> {
+        diem::bcs::from_bytes(bytes).map_err(|err| err.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use diem::move_lang::name_pool::ConstPool;
+    use diem::prelude::*;
+    use lang::compiler::dialects::DialectName;
+
+    use crate::cache::sha3;
+    use crate::cmd::build::{Build, BuildInfo, BUILD_INFO};
+    use crate::context::Context;
+    use crate::index::meta::module_id;
+    use crate::manifest::DoveToml;
+
+    #[test]
+    fn test_build_info() {
+        let _pool = ConstPool::new();
+        let project_dir =
+            std::env::temp_dir().join(format!("dove_build_{}", rand::random::<u32>()));
+        fs::create_dir_all(project_dir.join("modules")).unwrap();
+        fs::create_dir_all(project_dir.join("scripts")).unwrap();
+        fs::write(
+            project_dir.join("modules").join("coins.move"),
+            "address 0x1 { module Coins { use 0x1::Event; \
+             public fun value(): u64 { Event::emit(); 1 } } }",
+        )
+        .unwrap();
+        fs::write(
+            project_dir.join("modules").join("event.move"),
+            "address 0x1 { module Event { public fun emit() {} } }",
+        )
+        .unwrap();
+        fs::write(
+            project_dir.join("scripts").join("main.move"),
+            "script { use 0x1::Coins; fun main() { let _ = Coins::value(); } }",
+        )
+        .unwrap();
+
+        let ctx = Context {
+            project_dir: project_dir.clone(),
+            manifest: DoveToml::default(),
+            dialect: DialectName::DFinance.get_dialect(),
+            workspace_dir: None,
+            profile: None,
+        };
+        let build = Build {
+            tree: false,
+            package: false,
+            output: None,
+            exclude: vec![],
+            unordered: false,
+            watch: false,
+        };
+        build.build(&mut ctx.build_index().unwrap()).unwrap();
+
+        let info = fs::read_to_string(ctx.path_for(&ctx.manifest.layout.target).join(BUILD_INFO))
+            .unwrap();
+        let info = serde_json::from_str::<BuildInfo>(&info).unwrap();
+        let id = |name: &str| {
+            module_id(&ModuleId::new(
+                CORE_CODE_ADDRESS,
+                Identifier::new(name).unwrap(),
+            ))
+        };
+
+        let units = info
+            .units
+            .iter()
+            .map(|unit| (unit.kind.as_str(), unit.order))
+            .collect::<Vec<_>>();
+        assert_eq!(units, vec![("module", 0), ("module", 1), ("script", 0)]);
+
+        let event = &info.units[0];
+        assert_eq!(event.id, id("Event"));
+        assert!(event.imports.is_empty());
+        let coins = &info.units[1];
+        assert_eq!(coins.id, id("Coins"));
+        assert_eq!(coins.imports, vec![id("Event")]);
+        let script = &info.units[2];
+        assert_eq!(script.id, "main");
+        assert_eq!(script.imports, vec![id("Coins")]);
+
+        for unit in &info.units {
+            assert_eq!(unit.hash, sha3(&fs::read(&unit.path).unwrap()));
+        }
+
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
 }
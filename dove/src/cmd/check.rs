@@ -0,0 +1,232 @@
+use std::str::FromStr;
+
+use anyhow::Error;
+use diem::move_ir_types::location::Loc;
+use diem::move_lang::errors::{output_errors, Errors, FilesSourceText};
+use diem::move_lang::name_pool::ConstPool;
+use serde::Serialize;
+use structopt::StructOpt;
+use termcolor::{ColorChoice, StandardStream};
+
+use lang::checker::MoveChecker;
+use lang::compiler::file::{load_move_files, MoveFile};
+use lang::compiler::location::File;
+
+use crate::cmd::{Cmd, load_dependencies};
+use crate::context::Context;
+
+/// Type-check project sources.
+#[derive(StructOpt, Debug)]
+pub struct Check {
+    #[structopt(
+        help = "Error output format: human or json. \
+        The json format prints one diagnostic object per line.",
+        long = "message-format",
+        default_value = "human"
+    )]
+    message_format: MessageFormat,
+}
+
+/// Error output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for MessageFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(MessageFormat::Human),
+            "json" => Ok(MessageFormat::Json),
+            _ => Err(anyhow!(
+                "Invalid message format {:?}. Expected human or json.",
+                s
+            )),
+        }
+    }
+}
+
+impl Cmd for Check {
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        let projects = ctx.projects()?;
+        let mut failed = vec![];
+        for project in projects.iter() {
+            if project.workspace_dir.is_some() && self.message_format == MessageFormat::Human {
+                println!("Check:[{}]", project.project_name());
+            }
+            if !self.check(project)? {
+                failed.push(project.project_name());
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("could not compile:{}", failed.join(", ")))
+        }
+    }
+}
+
+impl Check {
+    /// Type-checks modules, scripts and tests of the project.
+    /// Returns `false` if the project has errors.
+    fn check(&self, ctx: &Context) -> Result<bool, Error> {
+        let dirs = ctx
+            .paths_for(&[
+                &ctx.manifest.layout.script_dir,
+                &ctx.manifest.layout.module_dir,
+                &ctx.manifest.layout.tests_dir,
+            ])
+            .into_iter()
+            .filter(|dir| dir.exists())
+            .collect::<Vec<_>>();
+
+        let mut index = ctx.build_index()?;
+        let dep_set = index.make_dependency_set(&dirs)?;
+        let dep_senders = index.dep_senders(&dep_set)?;
+        let dep_list = load_dependencies(dep_set)?;
+        let source_list = load_move_files(&dirs)?;
+
+        let sender = ctx.account_address()?;
+        let errors = match MoveChecker::new(ctx.dialect.as_ref(), Some(&sender))
+            .with_addresses(ctx.named_addresses()?)
+            .with_dep_senders(dep_senders)
+            .check(&source_list, &dep_list)
+        {
+            Ok(_) => return Ok(true),
+            Err(errors) => errors,
+        };
+
+        let files = source_files(source_list.iter().chain(&dep_list));
+        match self.message_format {
+            MessageFormat::Human => {
+                let mut writer = StandardStream::stderr(ColorChoice::Auto);
+                output_errors(&mut writer, files, errors);
+            }
+            MessageFormat::Json => {
+                for diagnostic in diagnostics(&files, errors) {
+                    println!("{}", serde_json::to_string(&diagnostic)?);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Returns the source texts of the files.
+/// Checker errors are already mapped to the original sources, so the file contents are used as is.
+fn source_files<'a, I: Iterator<Item = &'a MoveFile<'a, 'a>>>(files: I) -> FilesSourceText {
+    files
+        .map(|file| (ConstPool::push(file.name()), file.content().to_owned()))
+        .collect()
+}
+
+/// Compiler diagnostic in the json format.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct Diagnostic {
+    /// Diagnostic level.
+    level: &'static str,
+    /// Primary message.
+    message: String,
+    /// Source spans of the diagnostic. The first span is the primary one.
+    spans: Vec<DiagnosticSpan>,
+}
+
+/// Source span of the diagnostic.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct DiagnosticSpan {
+    file: String,
+    byte_start: usize,
+    byte_end: usize,
+    /// 1-based line of the span start.
+    line_start: u32,
+    /// 1-based column of the span start.
+    column_start: u32,
+    /// 1-based line of the span end.
+    line_end: u32,
+    /// 1-based column of the span end.
+    column_end: u32,
+    label: String,
+}
+
+/// Converts errors to the json diagnostics.
+fn diagnostics(files: &FilesSourceText, errors: Errors) -> Vec<Diagnostic> {
+    errors
+        .into_iter()
+        .filter(|error| !error.is_empty())
+        .map(|error| Diagnostic {
+            level: "error",
+            message: error[0].1.to_owned(),
+            spans: error
+                .into_iter()
+                .map(|(loc, label)| span(files, loc, label))
+                .collect(),
+        })
+        .collect()
+}
+
+fn span(files: &FilesSourceText, loc: Loc, label: String) -> DiagnosticSpan {
+    let source = files.get(loc.file()).map(|s| s.as_str()).unwrap_or("");
+    let file = File::new(source);
+    let position = |index| {
+        file.position(index)
+            .map(|pos| (pos.line + 1, pos.character + 1))
+            .unwrap_or((0, 0))
+    };
+    let (line_start, column_start) = position(loc.span().start());
+    let (line_end, column_end) = position(loc.span().end());
+    DiagnosticSpan {
+        file: loc.file().to_owned(),
+        byte_start: loc.span().start().to_usize(),
+        byte_end: loc.span().end().to_usize(),
+        line_start,
+        column_start,
+        line_end,
+        column_end,
+        label,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use diem::move_ir_types::location::{Loc, Span};
+    use diem::move_lang::name_pool::ConstPool;
+
+    use crate::cmd::check::diagnostics;
+
+    #[test]
+    fn test_json_diagnostics() {
+        let _pool = ConstPool::new();
+        let name = ConstPool::push("scripts/main.move");
+        let mut files = HashMap::new();
+        files.insert(name, "script {\n    fun main() { x }\n}\n".to_owned());
+
+        let errors = vec![vec![
+            (
+                Loc::new(name, Span::new(26, 27)),
+                "Unbound variable 'x'".to_owned(),
+            ),
+            (
+                Loc::new(name, Span::new(13, 17)),
+                "In this function".to_owned(),
+            ),
+        ]];
+        let diagnostics = diagnostics(&files, errors);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            serde_json::to_string(&diagnostics[0]).unwrap(),
+            "{\"level\":\"error\",\"message\":\"Unbound variable 'x'\",\"spans\":[\
+             {\"file\":\"scripts/main.move\",\"byte_start\":26,\"byte_end\":27,\
+             \"line_start\":2,\"column_start\":18,\"line_end\":2,\"column_end\":19,\
+             \"label\":\"Unbound variable 'x'\"},\
+             {\"file\":\"scripts/main.move\",\"byte_start\":13,\"byte_end\":17,\
+             \"line_start\":2,\"column_start\":5,\"line_end\":2,\"column_end\":9,\
+             \"label\":\"In this function\"}]}"
+        );
+    }
+}
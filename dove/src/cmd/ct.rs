@@ -8,17 +8,14 @@ use lang::builder::{Artifacts, MoveBuilder};
 use termcolor::{StandardStream, ColorChoice};
 use diem::move_core_types::language_storage::TypeTag;
 use serde::{Serialize, Deserialize};
-use diem::account::AccountAddress;
 use diem::move_lang::parser::lexer::{Lexer, Tok};
-use diem::move_lang::parser::syntax::parse_type;
 use diem::{
     prelude::CompiledUnit,
     move_lang::{compiled_unit, errors::output_errors},
 };
-use move_resource_viewer::tte::unwrap_spanned_ty;
-use std::fmt::Debug;
-use std::str::FromStr;
-use lang::compiler::ss58::{ss58_to_libra, replace_ss58_addresses};
+use lang::compiler::ss58::replace_ss58_addresses;
+use lang::script_args::{parse_type_param, parse_type_params};
+pub use lang::script_args::ScriptArg;
 use std::fs;
 
 /// Create transaction.
@@ -84,8 +81,7 @@ impl<'a> TransactionBuilder<'a> {
         if let Some(cmd_type_parameters) = cmd.type_parameters {
             type_parameters = cmd_type_parameters
                 .iter()
-                .map(|tp| replace_ss58_addresses(tp, &mut Default::default()))
-                .map(|tp| parse_type_params(&mut Lexer::new(&tp, "tp", Default::default())))
+                .map(|tp| parse_type_param(tp))
                 .collect::<Result<_, _>>()?;
         }
 
@@ -211,9 +207,14 @@ impl<'a> TransactionBuilder<'a> {
         }
 
         let sender = self.dove_ctx.account_address()?;
+        let addresses = self.dove_ctx.named_addresses()?;
         let script = MoveFile::load(&file_path)?;
-        let mut scripts =
-            ScriptMetadata::extract(self.dove_ctx.dialect.as_ref(), Some(&sender), &script)?;
+        let mut scripts = ScriptMetadata::extract(
+            self.dove_ctx.dialect.as_ref(),
+            Some(&sender),
+            &addresses,
+            &script,
+        )?;
         if scripts.is_empty() {
             return Err(anyhow!("Script not found in file '{}'", fname));
         }
@@ -250,6 +251,7 @@ impl<'a> TransactionBuilder<'a> {
             .dove_ctx
             .path_for(&self.dove_ctx.manifest.layout.script_dir);
         let sender = self.dove_ctx.account_address()?;
+        let addresses = self.dove_ctx.named_addresses()?;
         let mut files = find_move_files(&script_path)?
             .iter()
             .map(MoveFile::load)
@@ -267,8 +269,13 @@ impl<'a> TransactionBuilder<'a> {
                 }
             })
             .map(|mf| {
-                ScriptMetadata::extract(self.dove_ctx.dialect.as_ref(), Some(&sender), &mf)
-                    .map(|meta| (mf, meta))
+                ScriptMetadata::extract(
+                    self.dove_ctx.dialect.as_ref(),
+                    Some(&sender),
+                    &addresses,
+                    &mf,
+                )
+                .map(|meta| (mf, meta))
             })
             .filter_map(|script| match script {
                 Ok((mf, meta)) => Some((mf, meta)),
@@ -328,8 +335,13 @@ impl<'a> TransactionBuilder<'a> {
         if files.len() == 1 {
             let mf = MoveFile::load(&files[0])?;
             let sender = self.dove_ctx.account_address()?;
-            let mut meta =
-                ScriptMetadata::extract(self.dove_ctx.dialect.as_ref(), Some(&sender), &mf)?;
+            let addresses = self.dove_ctx.named_addresses()?;
+            let mut meta = ScriptMetadata::extract(
+                self.dove_ctx.dialect.as_ref(),
+                Some(&sender),
+                &addresses,
+                &mf,
+            )?;
             if meta.is_empty() {
                 return Err(anyhow!("Script not found."));
             }
@@ -353,12 +365,15 @@ impl<'a> TransactionBuilder<'a> {
             .ok_or_else(|| anyhow!("Failed to convert module dir path"))?;
 
         let dep_set = index.make_dependency_set(&[module_dir.as_str(), script.name()])?;
+        let dep_senders = index.dep_senders(&dep_set)?;
         let mut dep_list = load_dependencies(dep_set)?;
         dep_list.extend(load_move_files(&[module_dir])?);
 
         let sender = self.dove_ctx.account_address()?;
         let Artifacts { files, prog } =
             MoveBuilder::new(self.dove_ctx.dialect.as_ref(), Some(sender).as_ref())
+                .with_addresses(self.dove_ctx.named_addresses()?)
+                .with_dep_senders(dep_senders)
                 .build(&[script], &dep_list);
 
         match prog {
@@ -390,88 +405,24 @@ impl<'a> TransactionBuilder<'a> {
     ) -> Result<(usize, usize, Vec<ScriptArg>), Error> {
         let total_args = args_type.len();
 
-        fn parse_err<D: Debug>(name: &str, tp: &str, index: usize, value: &str, err: D) -> Error {
-            anyhow!(
-                "Parameter '{}' has {} type. Failed to parse {} [{}]. Error:'{:?}'",
-                name,
-                tp,
-                value,
-                index,
-                err
-            )
-        }
-
         args_type.iter().try_fold(
             (0, 0, Vec::new()),
-            |(signers, args_index, mut values), (name, tp)| match tp.as_str() {
-                "&signer" => Ok((signers + 1, args_index, values)),
-                "bool" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    values.push(ScriptArg::Bool(
-                        arg.parse()
-                            .map_err(|err| parse_err(name, tp, args_index, arg, err))?,
-                    ));
-                    Ok((signers, args_index + 1, values))
-                }
-                "u8" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    values.push(ScriptArg::U8(
-                        arg.parse()
-                            .map_err(|err| parse_err(name, tp, args_index, arg, err))?,
-                    ));
-                    Ok((signers, args_index + 1, values))
-                }
-                "u64" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    values.push(ScriptArg::U64(
-                        arg.parse()
-                            .map_err(|err| parse_err(name, tp, args_index, arg, err))?,
-                    ));
-                    Ok((signers, args_index + 1, values))
-                }
-                "u128" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    values.push(ScriptArg::U128(
-                        arg.parse()
-                            .map_err(|err| parse_err(name, tp, args_index, arg, err))?,
-                    ));
-                    Ok((signers, args_index + 1, values))
-                }
-                "address" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    values.push(ScriptArg::Address(Address::from_str(arg)?.addr));
-                    Ok((signers, args_index + 1, values))
-                }
-                "vector<u8>" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    let buffer = if arg.contains('[') {
-                        parse_vec(arg, "u8")?
-                    } else {
-                        hex::decode(arg)?
-                    };
-                    values.push(ScriptArg::VectorU8(buffer));
-                    Ok((signers, args_index + 1, values))
-                }
-                "vector<u64>" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    values.push(ScriptArg::VectorU64(parse_vec(arg, "u64")?));
-                    Ok((signers, args_index + 1, values))
-                }
-                "vector<u128>" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    values.push(ScriptArg::VectorU128(parse_vec(arg, "u128")?));
-                    Ok((signers, args_index + 1, values))
+            |(signers, args_index, mut values), (name, tp)| {
+                if tp == "&signer" {
+                    return Ok((signers + 1, args_index, values));
                 }
-                "vector<address>" => {
-                    let arg = self.argument(args_index, total_args)?;
-                    let address = parse_vec::<Address>(arg, "vector<address>")?
-                        .iter()
-                        .map(|addr| addr.addr)
-                        .collect();
-                    values.push(ScriptArg::VectorAddress(address));
-                    Ok((signers, args_index + 1, values))
-                }
-                &_ => Err(anyhow!("Unexpected script parameter: {}", tp)),
+                let arg = self.argument(args_index, total_args)?;
+                values.push(ScriptArg::parse(tp, arg).map_err(|err| {
+                    anyhow!(
+                        "Parameter '{}' has {} type. Failed to parse {} [{}]. Error:'{:?}'",
+                        name,
+                        tp,
+                        arg,
+                        args_index,
+                        err
+                    )
+                })?);
+                Ok((signers, args_index + 1, values))
             },
         )
     }
@@ -525,31 +476,6 @@ impl<'a> TransactionBuilder<'a> {
     }
 }
 
-/// Script argument type.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, PartialOrd)]
-pub enum ScriptArg {
-    /// u8
-    U8(u8),
-    /// u64
-    U64(u64),
-    /// u128
-    U128(u128),
-    /// bool
-    Bool(bool),
-    /// address
-    Address(AccountAddress),
-    /// vector<u8>
-    VectorU8(Vec<u8>),
-    /// vector<u64>
-    VectorU64(Vec<u64>),
-    /// vector<u128>
-    VectorU128(Vec<u128>),
-    /// vector<bool>
-    VectorBool(Vec<bool>),
-    /// vector<address>
-    VectorAddress(Vec<AccountAddress>),
-}
-
 /// Transaction model.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Transaction {
@@ -576,50 +502,6 @@ impl Transaction {
     }
 }
 
-fn parse_type_params(lexer: &mut Lexer) -> Result<TypeTag, Error> {
-    let ty = parse_type(lexer).map_err(|err| Error::msg(format!("{:?}", err)))?;
-    unwrap_spanned_ty(ty)
-}
-
-fn parse_vec<E>(tkn: &str, tp_name: &str) -> Result<Vec<E>, Error>
-where
-    E: FromStr,
-{
-    let map_err = |err| Error::msg(format!("{:?}", err));
-
-    let mut lexer = Lexer::new(tkn, "vec", Default::default());
-    lexer.advance().map_err(map_err)?;
-
-    if lexer.peek() != Tok::LBracket {
-        return Err(anyhow!("Vector in format  [n1, n2, ..., nn] is expected."));
-    }
-    lexer.advance().map_err(map_err)?;
-
-    let mut elements = vec![];
-    while lexer.peek() != Tok::RBracket {
-        match lexer.peek() {
-            Tok::Comma => {
-                lexer.advance().map_err(map_err)?;
-                continue;
-            }
-            Tok::EOF => {
-                return Err(anyhow!("unexpected end of vector."));
-            }
-            _ => {
-                elements.push(E::from_str(lexer.content()).map_err(|_| {
-                    anyhow!(
-                        "Failed to parse vector element. {} type is expected. Actual:'{}'",
-                        tp_name,
-                        lexer.content()
-                    )
-                })?);
-                lexer.advance().map_err(map_err)?;
-            }
-        }
-    }
-    Ok(elements)
-}
-
 fn store_transaction(ctx: &Context, name: &str, tx: Transaction) -> Result<(), Error> {
     let tx_dir = ctx.path_for(&ctx.manifest.layout.transaction_output);
     if !tx_dir.exists() {
@@ -638,22 +520,6 @@ fn store_transaction(ctx: &Context, name: &str, tx: Transaction) -> Result<(), E
     Ok(fs::write(&tx_file, diem::bcs::to_bytes(&tx)?)?)
 }
 
-struct Address {
-    addr: AccountAddress,
-}
-
-impl FromStr for Address {
-    type Err = Error;
-
-    fn from_str(addr: &str) -> Result<Self, Self::Err> {
-        let addr = match ss58_to_libra(addr) {
-            Ok(addr) => AccountAddress::from_hex_literal(&addr)?,
-            Err(_) => AccountAddress::from_hex_literal(&addr)?,
-        };
-        Ok(Address { addr })
-    }
-}
-
 #[cfg(test)]
 mod test {
     use crate::cmd::ct::TransactionBuilder;
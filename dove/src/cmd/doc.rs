@@ -0,0 +1,893 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Error;
+use diem::move_lang::errors::output_errors;
+use diem::move_lang::name_pool::ConstPool;
+use diem::move_lang::parser::ast::{
+    Definition, Function, FunctionBody_, FunctionVisibility, Kind_, ModuleAccess_,
+    ModuleDefinition, ModuleMember, StructDefinition, StructFields, Type, Type_, Use,
+};
+use diem::move_lang::FileCommentMap;
+use diem::move_ir_types::location::Loc;
+use diem::prelude::*;
+use structopt::StructOpt;
+use termcolor::{ColorChoice, StandardStream};
+
+use lang::compiler::address::{NamedAddresses, ProvidedAccountAddress};
+use lang::compiler::parser::parse_file;
+use move_executor::constants::error_code;
+use move_executor::session::extract_script_doc_comments;
+
+use crate::cmd::Cmd;
+use crate::context::Context;
+use crate::index::move_dir_iter;
+
+/// Generate modules documentation.
+#[derive(StructOpt, Debug)]
+pub struct Doc {
+    #[structopt(
+        help = "Output format: md or html.",
+        long = "format",
+        default_value = "md"
+    )]
+    format: Format,
+    #[structopt(
+        help = "Output directory. Defaults to the 'doc' directory in the target directory. \
+                An existing non-empty directory is only reused if it was created by `dove doc`.",
+        short = "o",
+        long = "output"
+    )]
+    output: Option<PathBuf>,
+}
+
+/// Documentation format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Markdown,
+    Html,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Html => "html",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md" | "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            _ => Err(anyhow!("Invalid format {:?}. Expected md or html.", s)),
+        }
+    }
+}
+
+impl Cmd for Doc {
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        let dirs = ctx.paths_for(&[&ctx.manifest.layout.module_dir]);
+        let mut index = ctx.build_index()?;
+        let dep_set = index.make_dependency_set(&dirs)?;
+        let dep_senders = index.dep_senders(&dep_set)?;
+
+        let sender = ctx.account_address()?;
+        let addresses = ctx.named_addresses()?;
+
+        let mut modules = vec![];
+        for dir in &dirs {
+            for file in move_dir_iter(dir) {
+                modules.extend(file_docs(&ctx, file.path(), false, &sender, &addresses)?);
+            }
+        }
+        let mut deps = dep_set.iter().collect::<Vec<_>>();
+        deps.sort();
+        for dep in deps {
+            let dep_sender = dep_senders.get(dep.as_ref()).unwrap_or(&sender);
+            modules.extend(file_docs(
+                &ctx,
+                Path::new(dep.as_ref()),
+                true,
+                dep_sender,
+                &addresses,
+            )?);
+        }
+        modules.sort_by(|a, b| (a.dependency, &a.id).cmp(&(b.dependency, &b.id)));
+
+        let output = match self.output {
+            Some(output) => {
+                let output = ctx.path_for(output);
+                clean_output(&output)?;
+                output
+            }
+            None => {
+                let output = ctx.path_for(&ctx.manifest.layout.target).join("doc");
+                if output.exists() {
+                    fs::remove_dir_all(&output)?;
+                }
+                output
+            }
+        };
+        fs::create_dir_all(&output)?;
+
+        let docs = Docs {
+            project: ctx.project_name(),
+            modules,
+            format: self.format,
+        };
+        let mut generated = vec![];
+        for module in &docs.modules {
+            generated.push(docs.store(
+                &output,
+                &module.file_name(),
+                &docs.render_module(module),
+            )?);
+        }
+        generated.push(docs.store(&output, "index", &docs.render_index())?);
+        fs::write(output.join(GENERATED_LIST), generated.join("\n"))?;
+        Ok(())
+    }
+}
+
+/// List of the files generated by `dove doc` in the output directory.
+const GENERATED_LIST: &str = ".dove-doc";
+
+/// Removes the files generated by the previous `dove doc` run from the output directory.
+/// Refuses to write into an existing non-empty directory that was not created by `dove doc`.
+fn clean_output(output: &Path) -> Result<(), Error> {
+    if !output.exists() {
+        return Ok(());
+    }
+
+    let list = output.join(GENERATED_LIST);
+    if !list.exists() {
+        if output.read_dir()?.next().is_some() {
+            return Err(anyhow!(
+                "Output directory {:?} is not empty and was not created by `dove doc`.",
+                output
+            ));
+        }
+        return Ok(());
+    }
+
+    for name in fs::read_to_string(&list)?.lines() {
+        // Only plain file names are listed, anything else was not written by `dove doc`.
+        if Path::new(name).file_name() != Some(OsStr::new(name)) {
+            continue;
+        }
+        let path = output.join(name);
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+    }
+    fs::remove_file(list)?;
+    Ok(())
+}
+
+/// Module documentation.
+struct ModuleDoc {
+    id: String,
+    path: String,
+    dependency: bool,
+    doc: Vec<String>,
+    imports: Vec<String>,
+    structs: Vec<ItemDoc>,
+    functions: Vec<ItemDoc>,
+    constants: Vec<ConstantDoc>,
+}
+
+impl ModuleDoc {
+    fn file_name(&self) -> String {
+        self.id.replace("::", "_")
+    }
+}
+
+/// Documentation of the struct or function.
+struct ItemDoc {
+    name: String,
+    signature: String,
+    doc: Vec<String>,
+}
+
+/// Documentation of the constant.
+struct ConstantDoc {
+    name: String,
+    type_name: String,
+    value: String,
+    error_code: Option<u128>,
+    doc: Vec<String>,
+}
+
+/// Extracts documentation of the modules defined in the file.
+fn file_docs(
+    ctx: &Context,
+    path: &Path,
+    dependency: bool,
+    sender: &ProvidedAccountAddress,
+    addresses: &NamedAddresses,
+) -> Result<Vec<ModuleDoc>, Error> {
+    let name = ConstPool::push(path.to_str().unwrap_or("source"));
+    let source = fs::read_to_string(path)?;
+
+    let mut files = HashMap::new();
+    let (defs, comments, errors, _) = parse_file(
+        ctx.dialect.as_ref(),
+        &mut files,
+        name,
+        &source,
+        Some(sender),
+        addresses,
+    );
+    if !errors.is_empty() {
+        let mut writer = StandardStream::stderr(ColorChoice::Auto);
+        output_errors(&mut writer, files, errors);
+        return Err(anyhow!("Failed to parse move file:{}", name));
+    }
+
+    let source = files.get(name).map(|s| s.as_str()).unwrap_or("");
+    let extractor = DocExtractor {
+        path: name,
+        source,
+        comments: &comments,
+        dependency,
+    };
+    let mut modules = vec![];
+    for def in defs {
+        match def {
+            Definition::Module(module) => {
+                modules.push(extractor.module(sender.as_account_address(), &module));
+            }
+            Definition::Address(_, address, defs) => {
+                let address = AccountAddress::new(address.to_u8());
+                for module in defs {
+                    modules.push(extractor.module(address, &module));
+                }
+            }
+            Definition::Script(_) => {
+                // no-op
+            }
+        }
+    }
+    Ok(modules)
+}
+
+/// Documentation extractor of the single file.
+struct DocExtractor<'a> {
+    path: &'a str,
+    source: &'a str,
+    comments: &'a FileCommentMap,
+    dependency: bool,
+}
+
+impl<'a> DocExtractor<'a> {
+    fn module(&self, address: AccountAddress, module: &ModuleDefinition) -> ModuleDoc {
+        let mut doc = ModuleDoc {
+            id: format!("{}::{}", short_address(&address), module.name.0.value),
+            path: self.path.to_owned(),
+            dependency: self.dependency,
+            doc: self.doc(module.loc),
+            imports: vec![],
+            structs: vec![],
+            functions: vec![],
+            constants: vec![],
+        };
+
+        for member in &module.members {
+            match member {
+                ModuleMember::Use(u) => {
+                    let ident = match u {
+                        Use::Members(ident, _) => ident,
+                        Use::Module(ident, _) => ident,
+                    };
+                    let ident = &ident.0.value;
+                    let address = AccountAddress::new(ident.address.clone().to_u8());
+                    doc.imports.push(format!(
+                        "{}::{}",
+                        short_address(&address),
+                        ident.name.0.value
+                    ));
+                }
+                ModuleMember::Struct(def) => doc.structs.push(self.struct_doc(def)),
+                ModuleMember::Function(func) => {
+                    if let FunctionVisibility::Internal = func.visibility {
+                        continue;
+                    }
+                    doc.functions.push(self.function_doc(func));
+                }
+                ModuleMember::Constant(constant) => {
+                    let span = constant.value.loc.span();
+                    doc.constants.push(ConstantDoc {
+                        name: constant.name.value().to_owned(),
+                        type_name: type_name(&constant.signature),
+                        value: self
+                            .source
+                            .get(span.start().to_usize()..span.end().to_usize())
+                            .unwrap_or_default()
+                            .to_owned(),
+                        error_code: error_code(constant),
+                        doc: self.doc(constant.loc),
+                    })
+                }
+                ModuleMember::Spec(_) => {
+                    // no-op
+                }
+            }
+        }
+        doc.imports.sort();
+        doc.imports.dedup();
+        doc
+    }
+
+    fn struct_doc(&self, def: &StructDefinition) -> ItemDoc {
+        let name = def.name.0.value.to_owned();
+        let mut signature = format!(
+            "{}struct {}{}",
+            if def.resource_opt.is_some() {
+                "resource "
+            } else {
+                ""
+            },
+            name,
+            type_parameters(
+                def.type_parameters
+                    .iter()
+                    .map(|(name, kind)| (name.value.as_str(), &kind.value))
+            )
+        );
+        match &def.fields {
+            StructFields::Defined(fields) => {
+                signature.push_str(" {\n");
+                for (field, tp) in fields {
+                    signature.push_str(&format!("    {}: {},\n", field.0.value, type_name(tp)));
+                }
+                signature.push('}');
+            }
+            StructFields::Native(_) => signature.insert_str(0, "native "),
+        }
+
+        ItemDoc {
+            name,
+            signature,
+            doc: self.doc(def.loc),
+        }
+    }
+
+    fn function_doc(&self, func: &Function) -> ItemDoc {
+        let name = func.name.0.value.to_owned();
+        let parameters = func
+            .signature
+            .parameters
+            .iter()
+            .map(|(var, tp)| format!("{}: {}", var.0.value, type_name(tp)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut signature = format!(
+            "{}{}fun {}{}({})",
+            visibility(&func.visibility),
+            match func.body.value {
+                FunctionBody_::Native => "native ",
+                FunctionBody_::Defined(_) => "",
+            },
+            name,
+            type_parameters(
+                func.signature
+                    .type_parameters
+                    .iter()
+                    .map(|(name, kind)| (name.value.as_str(), &kind.value))
+            ),
+            parameters
+        );
+        if !matches!(func.signature.return_type.value, Type_::Unit) {
+            signature.push_str(&format!(": {}", type_name(&func.signature.return_type)));
+        }
+        if !func.acquires.is_empty() {
+            let acquires = func
+                .acquires
+                .iter()
+                .map(|access| access_name(&access.value))
+                .collect::<Vec<_>>();
+            signature.push_str(&format!(" acquires {}", acquires.join(", ")));
+        }
+
+        ItemDoc {
+            name,
+            signature,
+            doc: self.doc(func.loc),
+        }
+    }
+
+    /// Returns `///` doc comments of the item.
+    fn doc(&self, loc: Loc) -> Vec<String> {
+        extract_script_doc_comments(loc, self.source, self.comments)
+    }
+}
+
+/// Documentation renderer.
+struct Docs {
+    project: String,
+    modules: Vec<ModuleDoc>,
+    format: Format,
+}
+
+impl Docs {
+    /// Stores the page and returns its file name.
+    fn store(&self, output: &Path, name: &str, content: &str) -> Result<String, Error> {
+        let file_name = format!("{}.{}", name, self.format.extension());
+        let path = output.join(&file_name);
+        fs::write(&path, content)?;
+        println!("Store: {:?}", path.as_os_str());
+        Ok(file_name)
+    }
+
+    /// Returns link to the documented module or the module identifier.
+    fn module_link(&self, id: &str) -> String {
+        match self.modules.iter().find(|module| module.id == id) {
+            Some(module) => {
+                let href = format!("{}.{}", module.file_name(), self.format.extension());
+                match self.format {
+                    Format::Markdown => format!("[`{}`]({})", id, href),
+                    Format::Html => {
+                        format!("<a href=\"{}\"><code>{}</code></a>", href, escape(id))
+                    }
+                }
+            }
+            None => self.code(id),
+        }
+    }
+
+    /// Formats code for the table cell.
+    fn code_cell(&self, text: &str) -> String {
+        match self.format {
+            Format::Markdown => format!("`{}`", text.replace('|', "\\|")),
+            Format::Html => self.code(text),
+        }
+    }
+
+    fn index_link(&self) -> String {
+        let href = format!("index.{}", self.format.extension());
+        match self.format {
+            Format::Markdown => format!("[index]({})", href),
+            Format::Html => format!("<a href=\"{}\">index</a>", href),
+        }
+    }
+
+    fn code(&self, text: &str) -> String {
+        match self.format {
+            Format::Markdown => format!("`{}`", text),
+            Format::Html => format!("<code>{}</code>", escape(text)),
+        }
+    }
+
+    fn render_index(&self) -> String {
+        let mut page = Page::new(self.format, &format!("{} documentation", self.project));
+        page.heading(1, &format!("{} documentation", self.project));
+
+        let (modules, deps): (Vec<_>, Vec<_>) =
+            self.modules.iter().partition(|module| !module.dependency);
+        page.heading(2, "Modules");
+        page.list(modules.iter().map(|module| self.module_link(&module.id)));
+        if !deps.is_empty() {
+            page.heading(2, "Dependencies");
+            page.list(deps.iter().map(|module| self.module_link(&module.id)));
+        }
+        page.finish()
+    }
+
+    fn render_module(&self, module: &ModuleDoc) -> String {
+        let mut page = Page::new(self.format, &module.id);
+        page.heading(1, &format!("Module {}", self.code(&module.id)));
+        page.paragraph(&module.doc);
+        page.raw_paragraph(&format!(
+            "{}: {} ({})",
+            if module.dependency {
+                "Dependency"
+            } else {
+                "Source"
+            },
+            self.code(&module.path),
+            self.index_link()
+        ));
+
+        if !module.imports.is_empty() {
+            page.heading(2, "Imports");
+            page.list(module.imports.iter().map(|id| self.module_link(id)));
+        }
+
+        if !module.structs.is_empty() {
+            page.heading(2, "Structs");
+            for item in &module.structs {
+                page.heading(3, &self.code(&item.name));
+                page.paragraph(&item.doc);
+                page.code_block(&item.signature);
+            }
+        }
+
+        if !module.functions.is_empty() {
+            page.heading(2, "Functions");
+            for item in &module.functions {
+                page.heading(3, &self.code(&item.name));
+                page.paragraph(&item.doc);
+                page.code_block(&item.signature);
+            }
+        }
+
+        if !module.constants.is_empty() {
+            page.heading(2, "Constants");
+            page.table(
+                &["Name", "Type", "Value", "Description"],
+                module.constants.iter().map(|constant| {
+                    vec![
+                        self.code_cell(&constant.name),
+                        self.code_cell(&constant.type_name),
+                        self.code_cell(&constant.value),
+                        page.inline(&constant.doc.join(" ")),
+                    ]
+                }),
+            );
+        }
+
+        let errors = module
+            .constants
+            .iter()
+            .filter_map(|constant| constant.error_code.map(|code| (code, constant)))
+            .collect::<BTreeMap<_, _>>();
+        if !errors.is_empty() {
+            page.heading(2, "Error codes");
+            page.table(
+                &["Code", "Name", "Description"],
+                errors.iter().map(|(code, constant)| {
+                    vec![
+                        code.to_string(),
+                        self.code_cell(&constant.name),
+                        page.inline(&constant.doc.join(" ")),
+                    ]
+                }),
+            );
+        }
+
+        page.finish()
+    }
+}
+
+/// Markdown or HTML page builder.
+struct Page {
+    format: Format,
+    out: String,
+}
+
+impl Page {
+    fn new(format: Format, title: &str) -> Page {
+        let out = match format {
+            Format::Markdown => String::new(),
+            Format::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n",
+                escape(title)
+            ),
+        };
+        Page { format, out }
+    }
+
+    /// Adds heading. The text is expected to be already formatted.
+    fn heading(&mut self, level: usize, text: &str) {
+        match self.format {
+            Format::Markdown => self
+                .out
+                .push_str(&format!("{} {}\n\n", "#".repeat(level), text)),
+            Format::Html => self
+                .out
+                .push_str(&format!("<h{l}>{}</h{l}>\n", text, l = level)),
+        }
+    }
+
+    /// Adds paragraph of the plain text lines.
+    fn paragraph(&mut self, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
+        match self.format {
+            Format::Markdown => self.out.push_str(&format!("{}\n\n", lines.join("\n"))),
+            Format::Html => self
+                .out
+                .push_str(&format!("<p>{}</p>\n", escape(&lines.join("\n")))),
+        }
+    }
+
+    /// Adds paragraph of the already formatted text.
+    fn raw_paragraph(&mut self, text: &str) {
+        match self.format {
+            Format::Markdown => self.out.push_str(&format!("{}\n\n", text)),
+            Format::Html => self.out.push_str(&format!("<p>{}</p>\n", text)),
+        }
+    }
+
+    /// Adds list of the already formatted items.
+    fn list<I: Iterator<Item = String>>(&mut self, items: I) {
+        match self.format {
+            Format::Markdown => {
+                for item in items {
+                    self.out.push_str(&format!("- {}\n", item));
+                }
+                self.out.push('\n');
+            }
+            Format::Html => {
+                self.out.push_str("<ul>\n");
+                for item in items {
+                    self.out.push_str(&format!("<li>{}</li>\n", item));
+                }
+                self.out.push_str("</ul>\n");
+            }
+        }
+    }
+
+    fn code_block(&mut self, code: &str) {
+        match self.format {
+            Format::Markdown => self.out.push_str(&format!("```move\n{}\n```\n\n", code)),
+            Format::Html => self
+                .out
+                .push_str(&format!("<pre><code>{}</code></pre>\n", escape(code))),
+        }
+    }
+
+    /// Adds table of the already formatted cells.
+    fn table<I: Iterator<Item = Vec<String>>>(&mut self, header: &[&str], rows: I) {
+        match self.format {
+            Format::Markdown => {
+                self.out.push_str(&format!("| {} |\n", header.join(" | ")));
+                self.out
+                    .push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+                for row in rows {
+                    self.out.push_str(&format!("| {} |\n", row.join(" | ")));
+                }
+                self.out.push('\n');
+            }
+            Format::Html => {
+                self.out.push_str("<table>\n<tr>");
+                for cell in header {
+                    self.out.push_str(&format!("<th>{}</th>", cell));
+                }
+                self.out.push_str("</tr>\n");
+                for row in rows {
+                    self.out.push_str("<tr>");
+                    for cell in row {
+                        self.out.push_str(&format!("<td>{}</td>", cell));
+                    }
+                    self.out.push_str("</tr>\n");
+                }
+                self.out.push_str("</table>\n");
+            }
+        }
+    }
+
+    /// Formats plain text for the inline use.
+    fn inline(&self, text: &str) -> String {
+        match self.format {
+            Format::Markdown => text.replace('|', "\\|"),
+            Format::Html => escape(text),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.format == Format::Html {
+            self.out.push_str("</body>\n</html>\n");
+        }
+        self.out
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns function visibility modifier with the trailing space.
+fn visibility(visibility: &FunctionVisibility) -> &'static str {
+    match visibility {
+        FunctionVisibility::Public(_) => "public ",
+        FunctionVisibility::Script(_) => "public(script) ",
+        FunctionVisibility::Internal => "",
+    }
+}
+
+/// Returns hex address without leading zeros.
+fn short_address(address: &AccountAddress) -> String {
+    let hex = hex::encode(address.to_u8());
+    let trimmed = hex.trim_start_matches('0');
+    if trimmed.is_empty() {
+        "0x0".to_owned()
+    } else {
+        format!("0x{}", trimmed)
+    }
+}
+
+fn type_parameters<'a, I: Iterator<Item = (&'a str, &'a Kind_)>>(params: I) -> String {
+    let params = params
+        .map(|(name, kind)| match kind {
+            Kind_::Resource => format!("{}: resource", name),
+            Kind_::Copyable => format!("{}: copyable", name),
+            _ => name.to_owned(),
+        })
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn access_name(access: &ModuleAccess_) -> String {
+    match access {
+        ModuleAccess_::Name(name) => name.value.to_owned(),
+        ModuleAccess_::ModuleAccess(module, name) => {
+            format!("{}::{}", module.0.value, name.value)
+        }
+        ModuleAccess_::QualifiedModuleAccess(module, name) => {
+            let module = &module.0.value;
+            format!(
+                "{}::{}::{}",
+                short_address(&AccountAddress::new(module.address.clone().to_u8())),
+                module.name.0.value,
+                name.value
+            )
+        }
+    }
+}
+
+fn type_name(tp: &Type) -> String {
+    let list = |types: &[Type]| types.iter().map(type_name).collect::<Vec<_>>().join(", ");
+    match &tp.value {
+        Type_::Apply(access, types) => {
+            if types.is_empty() {
+                access_name(&access.value)
+            } else {
+                format!("{}<{}>", access_name(&access.value), list(types))
+            }
+        }
+        Type_::Ref(is_mut, tp) => {
+            if *is_mut {
+                format!("&mut {}", type_name(tp))
+            } else {
+                format!("&{}", type_name(tp))
+            }
+        }
+        Type_::Fun(types, tp) => format!("({}):{}", list(types), type_name(tp)),
+        Type_::Unit => "()".to_owned(),
+        Type_::Multiple(types) => format!("({})", list(types)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use diem::move_lang::name_pool::ConstPool;
+    use diem::move_lang::parser::ast::Definition;
+    use diem::prelude::*;
+
+    use lang::compiler::address::NamedAddresses;
+    use lang::compiler::dialects::DialectName;
+    use lang::compiler::parser::parse_file;
+
+    use crate::cmd::doc::{clean_output, DocExtractor, Docs, Format, GENERATED_LIST};
+
+    #[test]
+    fn test_module_doc() {
+        let _pool = ConstPool::new();
+        let source = r"
+            address 0x1 {
+            /// Coins registry.
+            module Coins {
+                use 0x1::Signer;
+
+                /// Amount is zero.
+                const ERR_ZERO_AMOUNT: u64 = 101;
+
+                /// Coin balance.
+                resource struct Balance<Coin: copyable> {
+                    value: u128,
+                }
+
+                /// Deposits coins.
+                /// Aborts with `ERR_ZERO_AMOUNT`.
+                public fun deposit<Coin: copyable>(account: &signer, value: u128) acquires Balance {
+                    assert(value > 0, ERR_ZERO_AMOUNT);
+                    let balance = borrow_global_mut<Balance<Coin>>(Signer::address_of(account));
+                    balance.value = balance.value + value;
+                }
+
+                fun internal(): u64 { 1 }
+
+                public(script) fun transfer(_account: signer) {}
+            }
+            }
+        ";
+        let dialect = DialectName::DFinance.get_dialect();
+        let name = ConstPool::push("coins.move");
+        let mut files = HashMap::new();
+        let (defs, comments, errors, _) = parse_file(
+            dialect.as_ref(),
+            &mut files,
+            name,
+            source,
+            None,
+            &NamedAddresses::default(),
+        );
+        assert!(errors.is_empty());
+
+        let extractor = DocExtractor {
+            path: name,
+            source: &files[name],
+            comments: &comments,
+            dependency: false,
+        };
+        let module = match &defs[0] {
+            Definition::Address(_, _, modules) => {
+                extractor.module(CORE_CODE_ADDRESS, &modules[0])
+            }
+            _ => panic!("Expected address block"),
+        };
+
+        assert_eq!(module.id, "0x1::Coins");
+        assert_eq!(module.doc, vec!["Coins registry."]);
+        assert_eq!(module.imports, vec!["0x1::Signer"]);
+        assert_eq!(
+            module.structs[0].signature,
+            "resource struct Balance<Coin: copyable> {\n    value: u128,\n}"
+        );
+        assert_eq!(module.functions.len(), 2);
+        assert_eq!(
+            module.functions[0].signature,
+            "public fun deposit<Coin: copyable>(account: &signer, value: u128) acquires Balance"
+        );
+        assert_eq!(
+            module.functions[1].signature,
+            "public(script) fun transfer(_account: signer)"
+        );
+        assert_eq!(
+            module.functions[0].doc,
+            vec!["Deposits coins.", "Aborts with `ERR_ZERO_AMOUNT`."]
+        );
+        assert_eq!(module.constants[0].value, "101");
+        assert_eq!(module.constants[0].error_code, Some(101));
+
+        let docs = Docs {
+            project: "coins".to_owned(),
+            modules: vec![module],
+            format: Format::Markdown,
+        };
+        let page = docs.render_module(&docs.modules[0]);
+        assert!(page.starts_with("# Module `0x1::Coins`\n\nCoins registry.\n"));
+        assert!(page.contains("| 101 | `ERR_ZERO_AMOUNT` | Amount is zero. |"));
+        assert_eq!(docs.code_cell("1 | 2"), "`1 \\| 2`");
+        assert!(docs
+            .render_index()
+            .contains("- [`0x1::Coins`](0x1_Coins.md)"));
+    }
+
+    #[test]
+    fn test_clean_output() {
+        let dir = std::env::temp_dir().join(format!("dove_doc_{}", rand::random::<u32>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "keep").unwrap();
+        assert!(clean_output(&dir).is_err());
+
+        std::fs::write(dir.join("0x1_Coins.md"), "").unwrap();
+        std::fs::write(dir.join(GENERATED_LIST), "0x1_Coins.md\n../notes.txt").unwrap();
+        clean_output(&dir).unwrap();
+        assert!(!dir.join("0x1_Coins.md").exists());
+        assert!(!dir.join(GENERATED_LIST).exists());
+        assert!(dir.join("notes.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use anyhow::Error;
+use diem::move_lang::errors::output_errors;
+use diem::move_lang::name_pool::ConstPool;
+use diem::move_lang::parser::lexer::{Lexer, Tok};
+use diem::move_lang::strip_comments_and_verify;
+use structopt::StructOpt;
+use termcolor::{ColorChoice, StandardStream};
+
+use lang::compiler::address::{NamedAddresses, ProvidedAccountAddress};
+use lang::compiler::dialects::Dialect;
+use lang::compiler::parser::parse_file;
+
+use crate::cmd::Cmd;
+use crate::context::Context;
+use crate::index::move_dir_iter;
+
+/// Indentation unit.
+const INDENT: &str = "    ";
+
+/// Binary operators surrounded by spaces, the longest first.
+const OPERATORS: &[&str] = &[
+    "<==>", "==>", "==", "!=", "<=", ">=", "&&", "||", "+", "-", "%", "=",
+];
+
+/// Format move sources.
+#[derive(StructOpt, Debug)]
+pub struct Fmt {
+    #[structopt(
+        help = "Check that files are formatted without changing them. Exits with an error if any file needs formatting.",
+        long = "check"
+    )]
+    check: bool,
+    #[structopt(help = "Files to format. All project sources are formatted by default.")]
+    files: Vec<PathBuf>,
+}
+
+impl Cmd for Fmt {
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        let projects = ctx.projects()?;
+        let projects = projects.iter().collect::<Vec<_>>();
+
+        // Files given in the command line are formatted once with the member they belong to.
+        let files = if self.files.is_empty() {
+            projects
+                .iter()
+                .flat_map(|project| {
+                    source_files(project)
+                        .into_iter()
+                        .map(move |path| (*project, path))
+                })
+                .collect::<Vec<_>>()
+        } else {
+            self.files
+                .iter()
+                .map(|file| {
+                    let path = ctx.path_for(file);
+                    let project = projects
+                        .iter()
+                        .find(|project| path.starts_with(&project.project_dir))
+                        .copied()
+                        .unwrap_or(&ctx);
+                    (project, path)
+                })
+                .collect()
+        };
+
+        let mut unformatted = 0;
+        for (project, path) in files {
+            let source = fs::read_to_string(&path)?;
+            let formatted = format_file(project, &path, &source)?;
+            if formatted == source {
+                continue;
+            }
+
+            if self.check {
+                println!("Unformatted:[{}]", path.display());
+                unformatted += 1;
+            } else {
+                fs::write(&path, formatted)?;
+                println!("Formatted:[{}]", path.display());
+            }
+        }
+
+        if unformatted > 0 {
+            Err(anyhow!("{} file(s) need formatting", unformatted))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns all move files of the project layout.
+fn source_files(ctx: &Context) -> Vec<PathBuf> {
+    let mut files = ctx
+        .paths_for(&[
+            &ctx.manifest.layout.module_dir,
+            &ctx.manifest.layout.script_dir,
+            &ctx.manifest.layout.tests_dir,
+        ])
+        .into_iter()
+        .filter(|dir| dir.exists())
+        .flat_map(move_dir_iter)
+        .map(|entry| entry.into_path())
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Formats the file.
+/// The file is parsed before and after formatting, so the formatter never touches invalid
+/// sources and never changes the parsed program or its doc comments.
+fn format_file(ctx: &Context, path: &Path, source: &str) -> Result<String, Error> {
+    let addresses = ctx.named_addresses()?;
+    let dialect = ctx.dialect.as_ref();
+    // The sender only replaces the `{{sender}}` placeholder and does not change the tokens
+    // the formatted sources are compared by, so projects without an account address work too.
+    let sender = dialect.normalize_account_address("0x1")?;
+
+    let program = parse_program(dialect, path, source, &sender, &addresses)?;
+    let formatted = format_source(source);
+    if formatted != source {
+        let formatted_program = parse_program(dialect, path, &formatted, &sender, &addresses)
+            .map_err(|err| anyhow!("Failed to format {:?}: {}", path, err))?;
+        if program.tokens != formatted_program.tokens {
+            return Err(anyhow!(
+                "Failed to format {:?}: the parsed program was changed",
+                path
+            ));
+        }
+        if program.comments != formatted_program.comments {
+            return Err(anyhow!(
+                "Failed to format {:?}: doc comments were changed",
+                path
+            ));
+        }
+    }
+    Ok(formatted)
+}
+
+/// Parsed source file.
+struct ParsedProgram {
+    /// Tokens of the source without comments.
+    /// Sources with the same tokens parse to the same definitions.
+    tokens: Vec<(Tok, String)>,
+    /// Doc comments.
+    comments: Vec<String>,
+}
+
+/// Parses the source and returns its tokens and doc comments.
+fn parse_program(
+    dialect: &dyn Dialect,
+    path: &Path,
+    source: &str,
+    sender: &ProvidedAccountAddress,
+    addresses: &NamedAddresses,
+) -> Result<ParsedProgram, Error> {
+    let name = ConstPool::push(path.to_str().unwrap_or("source"));
+    let mut files = HashMap::new();
+    let (_, comments, errors, _) =
+        parse_file(dialect, &mut files, name, source, Some(sender), addresses);
+    if !errors.is_empty() {
+        let mut writer = StandardStream::stderr(ColorChoice::Auto);
+        output_errors(&mut writer, files, errors);
+        return Err(anyhow!("Failed to parse move file:{}", name));
+    }
+
+    let (source, _) = strip_comments_and_verify(name, &files[name])
+        .map_err(|errors| anyhow!("Failed to strip comments of {}: {:?}", name, errors))?;
+    let map_err = |err| Error::msg(format!("{:?}", err));
+    let mut lexer = Lexer::new(&source, name, Default::default());
+    let mut tokens = vec![];
+    lexer.advance().map_err(map_err)?;
+    while lexer.peek() != Tok::EOF {
+        tokens.push((lexer.peek(), lexer.content().to_owned()));
+        lexer.advance().map_err(map_err)?;
+    }
+
+    Ok(ParsedProgram {
+        tokens,
+        comments: comments
+            .values()
+            .map(|comment| comment.trim().to_owned())
+            .collect(),
+    })
+}
+
+/// Scanner state carried between lines.
+#[derive(Default)]
+struct Scanner {
+    in_comment: bool,
+    in_string: bool,
+}
+
+/// Formatted line.
+struct Line {
+    /// Line content without indentation.
+    text: String,
+    /// `true` if the line starts inside a block comment or a string.
+    /// Such lines keep their indentation and the text up to the end of the comment or the string.
+    verbatim: bool,
+    /// Brackets of the line.
+    brackets: Vec<char>,
+    /// Number of the closing brackets the line starts with.
+    leading_closes: usize,
+    /// First code character of the line.
+    first: Option<char>,
+    /// Last code character of the line.
+    last: Option<char>,
+}
+
+/// Formats the move source text.
+/// The formatter rewrites whitespace of the source text instead of printing the parsed AST:
+/// the parser keeps only doc comments in the comment map and drops locations of most tokens,
+/// so a printer would lose the regular comments and the original layout of expressions.
+/// `format_file` checks the result with the parser and its comment map instead.
+/// Only whitespace is changed:
+/// - lines are indented by the open brackets, continuation lines get an extra indent;
+/// - spaces between tokens are collapsed, spaces before `,`, `;` and `:` are removed
+///   and a space is added after `,` and `:`;
+/// - binary operators except `*`, `/`, `&`, `|`, `^`, `<`, `>`, `<<` and `>>`,
+///   which may also be unary or generic brackets, are surrounded by single spaces;
+/// - trailing whitespace and repeated or redundant blank lines are removed.
+/// Comments, strings and address literals are kept as written.
+pub fn format_source(source: &str) -> String {
+    let mut scanner = Scanner::default();
+    let lines = source
+        .replace("\r\n", "\n")
+        .lines()
+        .map(|line| scanner.line(line))
+        .collect::<Vec<_>>();
+
+    let mut out: Vec<String> = vec![];
+    // Indents of the lines inside the open brackets.
+    let mut stack: Vec<usize> = vec![];
+    let mut prev_last: Option<char> = None;
+    let mut prev_address = false;
+    let mut blank = false;
+    for line in lines {
+        if line.verbatim {
+            // The line keeps its indentation, but the code after the end of the comment
+            // or the string still opens and closes brackets.
+            let indent = stack.last().copied().unwrap_or(0);
+            for bracket in &line.brackets {
+                if matches!(bracket, '{' | '(' | '[') {
+                    stack.push(indent + 1);
+                } else {
+                    stack.pop();
+                }
+            }
+            out.push(line.text);
+            blank = false;
+            if line.last.is_some() {
+                prev_last = line.last;
+            }
+            continue;
+        }
+
+        if line.text.is_empty() {
+            blank = true;
+            continue;
+        }
+
+        let closes = line.leading_closes > 0;
+        let after_open = out.last().map(|prev| prev.ends_with('{')).unwrap_or(true);
+        if blank && !out.is_empty() && !closes && (!after_open || prev_address) {
+            out.push(String::new());
+        }
+        blank = false;
+
+        for _ in 0..line.leading_closes {
+            stack.pop();
+        }
+        let mut indent = stack.last().copied().unwrap_or(0);
+        let is_continuation = line.first.is_some()
+            && !closes
+            && line.first != Some('{')
+            && !line.text.starts_with("acquires ")
+            && !matches!(
+                prev_last,
+                None | Some(';') | Some('{') | Some('}') | Some(',') | Some('(') | Some('[')
+            );
+        if is_continuation {
+            indent += 1;
+        }
+
+        // Modules of the address blocks are not indented.
+        let is_address = line.text.starts_with("address ") && line.last == Some('{');
+        for bracket in &line.brackets[line.leading_closes..] {
+            if matches!(bracket, '{' | '(' | '[') {
+                stack.push(if is_address { indent } else { indent + 1 });
+            } else {
+                stack.pop();
+            }
+        }
+
+        out.push(format!("{}{}", INDENT.repeat(indent), line.text));
+        prev_address = is_address;
+        if line.last.is_some() {
+            prev_last = line.last;
+        }
+    }
+
+    let mut formatted = out.join("\n");
+    formatted.push('\n');
+    formatted
+}
+
+impl Scanner {
+    /// Normalizes spaces of the line and computes its brackets balance.
+    fn line(&mut self, line: &str) -> Line {
+        let verbatim = self.in_comment || self.in_string;
+        let line = if verbatim { line } else { line.trim() };
+
+        let mut result = Line {
+            text: String::with_capacity(line.len()),
+            verbatim,
+            brackets: vec![],
+            leading_closes: 0,
+            first: None,
+            last: None,
+        };
+        let out = &mut result.text;
+        let mut space = false;
+        let mut after_operator = false;
+        let mut leading = true;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.in_comment {
+                out.push(c);
+                if c == '*' && chars.peek() == Some(&'/') {
+                    out.push(chars.next().unwrap());
+                    self.in_comment = false;
+                }
+                continue;
+            }
+            if self.in_string {
+                out.push(c);
+                if c == '\\' {
+                    if let Some(next) = chars.next() {
+                        out.push(next);
+                    }
+                } else if c == '"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+
+            if c.is_whitespace() {
+                space = !out.is_empty();
+                continue;
+            }
+
+            // Type annotation colon, not a part of `::`.
+            let is_colon = c == ':' && chars.peek() != Some(&':') && !out.ends_with(':');
+            let operator = take_operator(c, &mut chars);
+            if matches!(c, ',' | ';' | ')' | ']')
+                || is_colon
+                || out.ends_with('(')
+                || out.ends_with('[')
+            {
+                space = false;
+            } else if out.ends_with(',')
+                || (c != ':' && out.ends_with(':') && !out.ends_with("::"))
+                || operator.is_some()
+                || after_operator
+            {
+                space = !out.is_empty();
+            }
+            if space {
+                out.push(' ');
+                space = false;
+            }
+
+            if c == '/' && chars.peek() == Some(&'/') {
+                out.push(c);
+                out.extend(chars.by_ref());
+                break;
+            }
+            match operator {
+                Some(operator) => out.push_str(operator),
+                None => out.push(c),
+            }
+            after_operator = operator.is_some();
+            if c == '/' && chars.peek() == Some(&'*') {
+                out.push(chars.next().unwrap());
+                self.in_comment = true;
+                leading = false;
+                continue;
+            }
+
+            match c {
+                '"' => self.in_string = true,
+                '{' | '(' | '[' => result.brackets.push(c),
+                '}' | ')' | ']' => {
+                    result.brackets.push(c);
+                    if leading {
+                        result.leading_closes += 1;
+                    }
+                }
+                _ => {}
+            }
+            leading = leading && matches!(c, '}' | ')' | ']');
+            if result.first.is_none() {
+                result.first = Some(c);
+            }
+            result.last = operator
+                .and_then(|operator| operator.chars().last())
+                .or(Some(c));
+        }
+
+        result.text = result.text.trim_end().to_owned();
+        result
+    }
+}
+
+/// Returns the operator starting with `c` and skips its remaining characters.
+fn take_operator(c: char, chars: &mut Peekable<Chars>) -> Option<&'static str> {
+    let text = std::iter::once(c)
+        .chain(chars.clone().take(3))
+        .collect::<String>();
+    let operator = *OPERATORS
+        .iter()
+        .find(|operator| text.starts_with(*operator))?;
+    for _ in 1..operator.len() {
+        chars.next();
+    }
+    Some(operator)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cmd::fmt::format_source;
+
+    #[test]
+    fn test_format_source() {
+        let source = "address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {\n\n\
+                      module Coins   {\n\
+                      use 0x1::Signer ;\n\n\n\
+                      /// Balance.\n\
+                      resource struct Balance { value: u128 ,}\n\
+                      public fun deposit(account:&signer,value :u128) acquires Balance {\n\
+                      \n\
+                      let balance = borrow_global_mut<Balance>(Signer::address_of(account));  \n\
+                      balance.value = balance.value +\n\
+                      value;   // add   value\n\
+                      /* keep   this */\n\
+                      let _ = x\"00  ff\";\n\
+                      }\n\
+                      \n\
+                      }\n\
+                      }";
+        let expected = "address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {\n\
+                        \n\
+                        module Coins {\n    \
+                        use 0x1::Signer;\n\
+                        \n    \
+                        /// Balance.\n    \
+                        resource struct Balance { value: u128, }\n    \
+                        public fun deposit(account: &signer, value: u128) acquires Balance {\n        \
+                        let balance = borrow_global_mut<Balance>(Signer::address_of(account));\n        \
+                        balance.value = balance.value +\n            \
+                        value; // add   value\n        \
+                        /* keep   this */\n        \
+                        let _ = x\"00  ff\";\n    \
+                        }\n\
+                        }\n\
+                        }\n";
+        let formatted = format_source(source);
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted), formatted);
+    }
+
+    #[test]
+    fn test_format_operators() {
+        let source = "module M {\nfun f(a :u64,v:&mut vector<u8>): bool {\n\
+                      *v=0x1::Vector::empty<u8>();\n\
+                      a+1>=2&&a%2!=0==>true\n}\n}\n";
+        assert_eq!(
+            format_source(source),
+            "module M {\n    fun f(a: u64, v: &mut vector<u8>): bool {\n        \
+             *v = 0x1::Vector::empty<u8>();\n        \
+             a + 1 >= 2 && a % 2 != 0 ==> true\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_code_after_comment() {
+        let source = "module M {\n/* a\n*/ fun f() {\nlet x = 1;\n}\nfun g() {}\n}\n";
+        let expected = "module M {\n    /* a\n*/ fun f() {\n        let x = 1;\n    }\n    \
+                        fun g() {}\n}\n";
+        assert_eq!(format_source(source), expected);
+        assert_eq!(format_source(expected), expected);
+    }
+
+    #[test]
+    fn test_format_block_comment() {
+        let source = "module M {\n/**\n   * Doc\n   */\nfun f() {}\n}\n";
+        assert_eq!(
+            format_source(source),
+            "module M {\n    /**\n   * Doc\n   */\n    fun f() {}\n}\n"
+        );
+    }
+}
@@ -50,7 +50,7 @@ impl Init {
 }
 
 impl Cmd for Init {
-    fn context(&self) -> Result<Context, Error> {
+    fn context(&self, _profile: Option<&str>) -> Result<Context, Error> {
         create_context()
     }
 
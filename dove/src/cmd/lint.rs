@@ -0,0 +1,915 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Error;
+use diem::move_lang::compiled_unit::CompiledUnit;
+use diem::move_lang::errors::{output_errors, Errors, FilesSourceText};
+use diem::move_lang::parser::ast::{
+    Bind, Bind_, Definition, Exp, Exp_, Function, FunctionBody_, FunctionVisibility,
+    ModuleAccess, ModuleAccess_, ModuleDefinition, ModuleMember, SequenceItem, SequenceItem_,
+    SpecBlock, SpecBlockMember_, StructFields, Type, Type_, Use,
+};
+use diem::move_lang::FileCommentMap;
+use diem::move_ir_types::location::Loc;
+use structopt::StructOpt;
+use termcolor::{ColorChoice, StandardStream};
+
+use lang::compiler::{compile, CheckerResult, CompileFlow, Step};
+use lang::compiler::file::load_move_files;
+use lang::compiler::address::ProvidedAccountAddress;
+use lang::compiler::parser::{set_dep_senders, ParserArtifact, ParsingMeta};
+use move_executor::session::extract_script_doc_comments;
+
+use crate::cmd::{Cmd, load_dependencies};
+use crate::context::Context;
+use crate::manifest::{LintLevel, Lints};
+
+/// Run linter.
+#[derive(StructOpt, Debug)]
+pub struct Lint {}
+
+impl Cmd for Lint {
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        let projects = ctx.projects()?;
+        let mut denied = 0;
+        for project in projects.iter() {
+            if project.workspace_dir.is_some() {
+                println!("Lint:[{}]", project.project_name());
+            }
+            denied += lint(project)?;
+        }
+
+        if denied > 0 {
+            Err(anyhow!("{} denied lint(s) found", denied))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Lints the project and returns the number of the denied lints.
+fn lint(ctx: &Context) -> Result<usize, Error> {
+    let dirs = ctx.paths_for(&[
+        &ctx.manifest.layout.script_dir,
+        &ctx.manifest.layout.module_dir,
+    ]);
+    let mut index = ctx.build_index()?;
+    let dep_set = index.make_dependency_set(&dirs)?;
+    let dep_senders = index.dep_senders(&dep_set)?;
+    let dep_list = load_dependencies(dep_set)?;
+    let source_list = load_move_files(&dirs)?;
+
+    let sender = ctx.account_address()?;
+    let addresses = ctx.named_addresses()?;
+    let LintResult { files, result } = compile(
+        ctx.dialect.as_ref(),
+        &source_list,
+        &dep_list,
+        Some(&sender),
+        &addresses,
+        LintFlow::new(&ctx.manifest.lint, dep_senders),
+    );
+
+    let mut writer = StandardStream::stderr(ColorChoice::Auto);
+    match result {
+        Err(errors) => {
+            output_errors(&mut writer, files, errors);
+            Err(anyhow!("could not compile:{}", ctx.project_name()))
+        }
+        Ok(report) => {
+            let count = report.warnings.len();
+            if count > 0 {
+                output_errors(&mut writer, files, report.warnings);
+            }
+            println!("{} lint(s) found", count);
+            Ok(report.denied)
+        }
+    }
+}
+
+/// Lint kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LintKind {
+    UnusedImports,
+    UnusedLocals,
+    UnusedConstants,
+    MagicAbortCodes,
+    MissingDocs,
+    UnmovedResources,
+}
+
+impl LintKind {
+    /// Name of the lint in the `[lint]` table.
+    fn name(&self) -> &'static str {
+        match self {
+            LintKind::UnusedImports => "unused_imports",
+            LintKind::UnusedLocals => "unused_locals",
+            LintKind::UnusedConstants => "unused_constants",
+            LintKind::MagicAbortCodes => "magic_abort_codes",
+            LintKind::MissingDocs => "missing_docs",
+            LintKind::UnmovedResources => "unmoved_resources",
+        }
+    }
+
+    /// Configured level of the lint.
+    fn level(&self, lints: &Lints) -> LintLevel {
+        match self {
+            LintKind::UnusedImports => lints.unused_imports,
+            LintKind::UnusedLocals => lints.unused_locals,
+            LintKind::UnusedConstants => lints.unused_constants,
+            LintKind::MagicAbortCodes => lints.magic_abort_codes,
+            LintKind::MissingDocs => lints.missing_docs,
+            LintKind::UnmovedResources => lints.unmoved_resources,
+        }
+    }
+}
+
+/// Reported lint.
+#[derive(Debug)]
+struct Warning {
+    kind: LintKind,
+    level: LintLevel,
+    loc: Loc,
+    message: String,
+}
+
+/// Lints of the project.
+struct Report {
+    /// Lints in the `output_errors` format.
+    warnings: Errors,
+    /// Number of the lints with the `deny` level.
+    denied: usize,
+}
+
+/// Result of the lint flow.
+struct LintResult {
+    files: FilesSourceText,
+    result: Result<Report, Errors>,
+}
+
+/// Compile flow which lints the parsed sources and stops after the CFGIR stage.
+/// Lints are reported only for the programs that pass the type checking and the CFGIR checks.
+/// They inspect the parsed definitions, since `use` declarations, constant names and doc comments
+/// are erased by the expansion and type-dependent checks are left to the compiler passes.
+struct LintFlow<'a> {
+    lints: &'a Lints,
+    /// Addresses of address-less modules of the dependency files.
+    dep_senders: HashMap<String, ProvidedAccountAddress>,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> LintFlow<'a> {
+    fn new(
+        lints: &'a Lints,
+        dep_senders: HashMap<String, ProvidedAccountAddress>,
+    ) -> LintFlow<'a> {
+        LintFlow {
+            lints,
+            dep_senders,
+            warnings: vec![],
+        }
+    }
+
+    fn report(&mut self, meta: ParsingMeta) -> LintResult {
+        let warnings = self.warnings.drain(..).collect::<Vec<_>>();
+        let denied = warnings
+            .iter()
+            .filter(|warning| warning.level == LintLevel::Deny)
+            .count();
+        let warnings = warnings
+            .into_iter()
+            .map(|warning| {
+                let level = match warning.level {
+                    LintLevel::Deny => "deny",
+                    _ => "warn",
+                };
+                vec![(
+                    warning.loc,
+                    format!("{} [{}({})]", warning.message, level, warning.kind.name()),
+                )]
+            })
+            .collect();
+
+        LintResult {
+            result: Ok(Report {
+                warnings: meta.offsets_map.transform(warnings),
+                denied,
+            }),
+            files: meta.source_map,
+        }
+    }
+}
+
+impl<'a> CompileFlow<LintResult> for LintFlow<'a> {
+    fn after_parsing(
+        &mut self,
+        mut parser_artifact: ParserArtifact,
+    ) -> Step<LintResult, ParserArtifact> {
+        if let Ok(program) = &mut parser_artifact.result {
+            set_dep_senders(program, &self.dep_senders);
+        }
+        if let Ok(program) = &parser_artifact.result {
+            let meta = &parser_artifact.meta;
+            let no_comments = FileCommentMap::default();
+            for def in &program.source_definitions {
+                let file = match def {
+                    Definition::Module(module) => module.loc.file(),
+                    Definition::Address(loc, _, _) => loc.file(),
+                    Definition::Script(script) => script.loc.file(),
+                };
+                let mut linter = Linter {
+                    lints: self.lints,
+                    source: meta.source_map.get(file).map(|s| s.as_str()).unwrap_or(""),
+                    comments: meta.comments.get(file).unwrap_or(&no_comments),
+                    warnings: vec![],
+                };
+                linter.definition(def);
+                self.warnings.extend(linter.warnings);
+            }
+        }
+        Step::Next(parser_artifact)
+    }
+
+    fn after_check(
+        &mut self,
+        meta: ParsingMeta,
+        check_result: CheckerResult,
+    ) -> Step<LintResult, (ParsingMeta, CheckerResult)> {
+        Step::Stop(match check_result {
+            Ok(_) => self.report(meta),
+            Err(errors) => LintResult {
+                result: Err(meta.offsets_map.transform(errors)),
+                files: meta.source_map,
+            },
+        })
+    }
+
+    fn after_translate(
+        &mut self,
+        meta: ParsingMeta,
+        translation_result: Result<Vec<CompiledUnit>, Errors>,
+    ) -> LintResult {
+        match translation_result {
+            Ok(_) => self.report(meta),
+            Err(errors) => LintResult {
+                result: Err(meta.offsets_map.transform(errors)),
+                files: meta.source_map,
+            },
+        }
+    }
+}
+
+/// Linter of the single file definitions.
+struct Linter<'a> {
+    lints: &'a Lints,
+    source: &'a str,
+    comments: &'a FileCommentMap,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> Linter<'a> {
+    fn warn(&mut self, kind: LintKind, loc: Loc, message: String) {
+        let level = kind.level(self.lints);
+        if level != LintLevel::Allow {
+            self.warnings.push(Warning {
+                kind,
+                level,
+                loc,
+                message,
+            });
+        }
+    }
+
+    fn definition(&mut self, def: &Definition) {
+        match def {
+            Definition::Module(module) => self.module(module),
+            Definition::Address(_, _, modules) => {
+                for module in modules {
+                    self.module(module);
+                }
+            }
+            Definition::Script(script) => {
+                let mut usages = Usages::default();
+                for constant in &script.constants {
+                    usages.tp(&constant.signature);
+                    usages.exp(&constant.value);
+                }
+                usages.merge(self.function(&script.function, false));
+
+                self.uses(script.uses.iter(), &usages);
+                self.constants(
+                    script
+                        .constants
+                        .iter()
+                        .map(|constant| (&constant.name.0.value, constant.name.0.loc)),
+                    &usages,
+                );
+                self.abort_codes(&usages);
+            }
+        }
+    }
+
+    fn module(&mut self, module: &ModuleDefinition) {
+        let mut usages = Usages::default();
+        for member in &module.members {
+            match member {
+                ModuleMember::Function(func) => usages.merge(self.function(func, true)),
+                ModuleMember::Struct(def) => {
+                    if let StructFields::Defined(fields) = &def.fields {
+                        for (_, tp) in fields {
+                            usages.tp(tp);
+                        }
+                    }
+                }
+                ModuleMember::Constant(constant) => {
+                    usages.tp(&constant.signature);
+                    usages.exp(&constant.value);
+                }
+                ModuleMember::Spec(spec) => {
+                    let mut spec_usages = Usages::default();
+                    spec_usages.spec(spec);
+                    usages.merge(spec_usages);
+                }
+                ModuleMember::Use(_) => {
+                    // no-op
+                }
+            }
+        }
+
+        self.uses(
+            module.members.iter().filter_map(|member| match member {
+                ModuleMember::Use(u) => Some(u),
+                _ => None,
+            }),
+            &usages,
+        );
+        self.constants(
+            module.members.iter().filter_map(|member| match member {
+                ModuleMember::Constant(constant) => {
+                    Some((&constant.name.0.value, constant.name.0.loc))
+                }
+                _ => None,
+            }),
+            &usages,
+        );
+        self.abort_codes(&usages);
+
+        for member in &module.members {
+            if let ModuleMember::Struct(def) = member {
+                let name = &def.name.0.value;
+                if def.resource_opt.is_some()
+                    && usages.moved_to.contains(name)
+                    && !usages.moved_from.contains(name)
+                    && !usages.unknown_move_from
+                {
+                    self.warn(
+                        LintKind::UnmovedResources,
+                        def.name.0.loc,
+                        format!(
+                            "Resource '{}' is moved to the storage but never moved out",
+                            name
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Lints the function and returns its usages.
+    fn function(&mut self, func: &Function, is_module_function: bool) -> Usages {
+        let name = &func.name.0;
+        if is_module_function
+            && !matches!(func.visibility, FunctionVisibility::Internal)
+            && extract_script_doc_comments(func.loc, self.source, self.comments).is_empty()
+        {
+            self.warn(
+                LintKind::MissingDocs,
+                name.loc,
+                format!(
+                    "Missing doc comment for the public function '{}'",
+                    name.value
+                ),
+            );
+        }
+
+        let mut usages = Usages::default();
+        for (_, tp) in &func.signature.parameters {
+            usages.tp(tp);
+        }
+        usages.tp(&func.signature.return_type);
+        for access in &func.acquires {
+            usages.access(access);
+        }
+        if let FunctionBody_::Defined((_, seq, _, exp)) = &func.body.value {
+            usages.block(seq, (**exp).as_ref());
+        }
+
+        for (local, loc) in &usages.locals {
+            if !local.starts_with('_') && !usages.names.contains(local) {
+                self.warn(
+                    LintKind::UnusedLocals,
+                    *loc,
+                    format!("Unused local variable '{}'", local),
+                );
+            }
+        }
+        usages
+    }
+
+    fn uses<'b, I: Iterator<Item = &'b Use>>(&mut self, uses: I, usages: &Usages) {
+        for u in uses {
+            match u {
+                Use::Module(ident, alias) => {
+                    let name = alias
+                        .as_ref()
+                        .map(|alias| &alias.0)
+                        .unwrap_or(&ident.0.value.name.0);
+                    if !usages.modules.contains(&name.value) {
+                        self.warn(
+                            LintKind::UnusedImports,
+                            ident.0.loc,
+                            format!("Unused import '{}'", name.value),
+                        );
+                    }
+                }
+                Use::Members(ident, members) => {
+                    for (member, alias) in members {
+                        let name = alias.as_ref().unwrap_or(member);
+                        let used = if member.value == "Self" {
+                            let module = alias.as_ref().unwrap_or(&ident.0.value.name.0);
+                            usages.modules.contains(&module.value)
+                        } else {
+                            usages.names.contains(&name.value)
+                        };
+                        if !used {
+                            self.warn(
+                                LintKind::UnusedImports,
+                                member.loc,
+                                format!("Unused import '{}'", name.value),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn constants<'b, I: Iterator<Item = (&'b String, Loc)>>(
+        &mut self,
+        constants: I,
+        usages: &Usages,
+    ) {
+        for (name, loc) in constants {
+            if !usages.names.contains(name) {
+                self.warn(
+                    LintKind::UnusedConstants,
+                    loc,
+                    format!("Unused constant '{}'", name),
+                );
+            }
+        }
+    }
+
+    fn abort_codes(&mut self, usages: &Usages) {
+        for loc in &usages.magic_abort_codes {
+            self.warn(
+                LintKind::MagicAbortCodes,
+                *loc,
+                "Abort code is a magic number. Use a named `ERR_` constant instead".to_owned(),
+            );
+        }
+    }
+}
+
+/// Names and resources used by the definitions.
+#[derive(Default)]
+struct Usages {
+    /// Names of the accessed locals, constants, functions and structs.
+    names: HashSet<String>,
+    /// Names or aliases of the accessed modules.
+    modules: HashSet<String>,
+    /// Declared local variables.
+    locals: Vec<(String, Loc)>,
+    /// Locations of the abort codes given as number literals.
+    magic_abort_codes: Vec<Loc>,
+    /// Resources published by `move_to`.
+    moved_to: HashSet<String>,
+    /// Resources moved out by `move_from`.
+    moved_from: HashSet<String>,
+    /// `true` if `move_from` is called with an unknown type.
+    unknown_move_from: bool,
+}
+
+impl Usages {
+    /// Adds module level usages of the function. Locals are not merged.
+    fn merge(&mut self, other: Usages) {
+        self.names.extend(other.names);
+        self.modules.extend(other.modules);
+        self.magic_abort_codes.extend(other.magic_abort_codes);
+        self.moved_to.extend(other.moved_to);
+        self.moved_from.extend(other.moved_from);
+        self.unknown_move_from |= other.unknown_move_from;
+    }
+
+    fn access(&mut self, access: &ModuleAccess) {
+        match &access.value {
+            ModuleAccess_::Name(name) => {
+                self.names.insert(name.value.to_owned());
+            }
+            ModuleAccess_::ModuleAccess(module, _) => {
+                self.modules.insert(module.0.value.to_owned());
+            }
+            ModuleAccess_::QualifiedModuleAccess(_, _) => {
+                // no-op
+            }
+        }
+    }
+
+    fn tp(&mut self, tp: &Type) {
+        match &tp.value {
+            Type_::Apply(access, types) => {
+                self.access(access);
+                self.types(types);
+            }
+            Type_::Ref(_, tp) => self.tp(tp),
+            Type_::Fun(types, tp) => {
+                self.types(types);
+                self.tp(tp);
+            }
+            Type_::Unit => {}
+            Type_::Multiple(types) => self.types(types),
+        }
+    }
+
+    fn types(&mut self, types: &[Type]) {
+        for tp in types {
+            self.tp(tp);
+        }
+    }
+
+    fn block(&mut self, seq: &[SequenceItem], exp: Option<&Exp>) {
+        for item in seq {
+            match &item.value {
+                SequenceItem_::Seq(exp) => self.exp(exp),
+                SequenceItem_::Declare(binds, tp) => {
+                    for bind in &binds.value {
+                        self.bind(bind);
+                    }
+                    if let Some(tp) = tp {
+                        self.tp(tp);
+                    }
+                }
+                SequenceItem_::Bind(binds, tp, exp) => {
+                    for bind in &binds.value {
+                        self.bind(bind);
+                    }
+                    if let Some(tp) = tp {
+                        self.tp(tp);
+                    }
+                    self.exp(exp);
+                }
+            }
+        }
+        if let Some(exp) = exp {
+            self.exp(exp);
+        }
+    }
+
+    fn bind(&mut self, bind: &Bind) {
+        match &bind.value {
+            Bind_::Var(var) => self.locals.push((var.0.value.to_owned(), var.0.loc)),
+            Bind_::Unpack(access, types, binds) => {
+                self.access(access);
+                if let Some(types) = types {
+                    self.types(types);
+                }
+                for (_, bind) in binds {
+                    self.bind(bind);
+                }
+            }
+        }
+    }
+
+    fn exp(&mut self, exp: &Exp) {
+        match &exp.value {
+            Exp_::Value(_)
+            | Exp_::Unit
+            | Exp_::Break
+            | Exp_::Continue
+            | Exp_::InferredNum(_)
+            | Exp_::UnresolvedError => { /*no op*/ }
+            Exp_::Spec(spec) => self.spec(spec),
+            // Lambda parameters are spec variables, not locals of the function.
+            Exp_::Lambda(_, e) => self.exp(e),
+            Exp_::Index(e, index) => {
+                self.exp(e);
+                self.exp(index);
+            }
+            Exp_::Move(var) | Exp_::Copy(var) => {
+                self.names.insert(var.0.value.to_owned());
+            }
+            Exp_::Name(access, types) => {
+                self.access(access);
+                if let Some(types) = types {
+                    self.types(types);
+                }
+            }
+            Exp_::Call(access, types, args) => {
+                self.call(access, types.as_deref(), &args.value);
+                self.access(access);
+                if let Some(types) = types {
+                    self.types(types);
+                }
+                for arg in &args.value {
+                    self.exp(arg);
+                }
+            }
+            Exp_::Pack(access, types, fields) => {
+                self.access(access);
+                if let Some(types) = types {
+                    self.types(types);
+                }
+                for (_, exp) in fields {
+                    self.exp(exp);
+                }
+            }
+            Exp_::IfElse(eb, et, ef) => {
+                self.exp(eb);
+                self.exp(et);
+                if let Some(ef) = ef {
+                    self.exp(ef);
+                }
+            }
+            Exp_::While(eb, eloop) => {
+                self.exp(eb);
+                self.exp(eloop);
+            }
+            Exp_::Block((_, seq, _, exp)) => self.block(seq, (**exp).as_ref()),
+            Exp_::ExpList(exps) => {
+                for exp in exps {
+                    self.exp(exp);
+                }
+            }
+            Exp_::Assign(a, e) | Exp_::BinopExp(a, _, e) => {
+                self.exp(a);
+                self.exp(e);
+            }
+            Exp_::Abort(e) => {
+                if is_literal(e) {
+                    self.magic_abort_codes.push(e.loc);
+                }
+                self.exp(e);
+            }
+            Exp_::Dereference(e)
+            | Exp_::Loop(e)
+            | Exp_::UnaryExp(_, e)
+            | Exp_::Borrow(_, e)
+            | Exp_::Dot(e, _) => self.exp(e),
+            Exp_::Annotate(e, tp) | Exp_::Cast(e, tp) => {
+                self.exp(e);
+                self.tp(tp);
+            }
+            Exp_::Return(e) => {
+                if let Some(e) = e {
+                    self.exp(e);
+                }
+            }
+        }
+    }
+
+    /// Adds usages of the spec block. Names used only in specs are used as well.
+    fn spec(&mut self, spec: &SpecBlock) {
+        for member in &spec.value.members {
+            match &member.value {
+                SpecBlockMember_::Condition { exp, .. }
+                | SpecBlockMember_::Include { exp, .. }
+                | SpecBlockMember_::Apply { exp, .. } => self.exp(exp),
+                SpecBlockMember_::Function {
+                    signature, body, ..
+                } => {
+                    for (_, tp) in &signature.parameters {
+                        self.tp(tp);
+                    }
+                    self.tp(&signature.return_type);
+                    if let FunctionBody_::Defined((_, seq, _, exp)) = &body.value {
+                        self.block(seq, (**exp).as_ref());
+                    }
+                }
+                SpecBlockMember_::Variable { type_, .. } => self.tp(type_),
+                _ => {}
+            }
+        }
+    }
+
+    /// Handles builtin function calls.
+    fn call(&mut self, access: &ModuleAccess, types: Option<&[Type]>, args: &[Exp]) {
+        let name = match &access.value {
+            ModuleAccess_::Name(name) => name.value.as_str(),
+            _ => return,
+        };
+        let type_name = types.and_then(|types| types.first()).and_then(struct_name);
+        match name {
+            "assert" => {
+                if let Some(code) = args.get(1) {
+                    if is_literal(code) {
+                        self.magic_abort_codes.push(code.loc);
+                    }
+                }
+            }
+            "move_to" | "move_to_sender" => {
+                let packed = args.last().and_then(|arg| match &arg.value {
+                    Exp_::Pack(access, _, _) => match &access.value {
+                        ModuleAccess_::Name(name) => Some(name.value.to_owned()),
+                        _ => None,
+                    },
+                    _ => None,
+                });
+                if let Some(name) = type_name.or(packed) {
+                    self.moved_to.insert(name);
+                }
+            }
+            "move_from" => match type_name {
+                Some(name) => {
+                    self.moved_from.insert(name);
+                }
+                None => self.unknown_move_from = true,
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Returns `true` if the expression is a number literal.
+fn is_literal(exp: &Exp) -> bool {
+    matches!(exp.value, Exp_::Value(_) | Exp_::InferredNum(_))
+}
+
+/// Returns the name of the struct defined in the current module.
+fn struct_name(tp: &Type) -> Option<String> {
+    match &tp.value {
+        Type_::Apply(access, _) => match &access.value {
+            ModuleAccess_::Name(name) => Some(name.value.to_owned()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use diem::move_lang::name_pool::ConstPool;
+
+    use lang::compiler::address::NamedAddresses;
+    use lang::compiler::dialects::DialectName;
+    use lang::compiler::parser::parse_file;
+
+    use crate::cmd::lint::{LintKind, Linter};
+    use crate::manifest::{LintLevel, Lints};
+
+    fn lint(source: &str, lints: &Lints) -> Vec<(LintKind, String)> {
+        let dialect = DialectName::DFinance.get_dialect();
+        let name = ConstPool::push("lint.move");
+        let mut files = HashMap::new();
+        let (defs, comments, errors, _) = parse_file(
+            dialect.as_ref(),
+            &mut files,
+            name,
+            source,
+            None,
+            &NamedAddresses::default(),
+        );
+        assert!(errors.is_empty());
+
+        let mut linter = Linter {
+            lints,
+            source: &files[name],
+            comments: &comments,
+            warnings: vec![],
+        };
+        for def in &defs {
+            linter.definition(def);
+        }
+        linter
+            .warnings
+            .into_iter()
+            .map(|warning| (warning.kind, warning.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_lints() {
+        let _pool = ConstPool::new();
+        let source = r"
+            address 0x1 {
+            module Coins {
+                use 0x1::Signer;
+                use 0x1::Event;
+                use 0x1::Vector;
+                use 0x1::Event::{emit};
+
+                const ERR_ZERO_AMOUNT: u64 = 101;
+                const UNUSED: u64 = 1;
+
+                resource struct Balance {
+                    value: u128,
+                }
+
+                resource struct Info {
+                    items: vector<u8>,
+                }
+
+                /// Publishes the balance.
+                public fun publish(account: &signer) {
+                    let _ignored = 1;
+                    move_to(account, Balance { value: 0 });
+                    move_to<Info>(account, Info { items: Vector::empty() });
+                }
+
+                public fun deposit(account: &signer, value: u128) acquires Balance {
+                    assert(value > 0, ERR_ZERO_AMOUNT);
+                    let unused = 1;
+                    let balance = borrow_global_mut<Balance>(Signer::address_of(account));
+                    balance.value = balance.value + value;
+                    if (value > 100) abort 42;
+                }
+
+                fun destroy(account: &signer): vector<u8> acquires Info {
+                    assert(true, 7);
+                    let Info { items } = move_from<Info>(Signer::address_of(account));
+                    items
+                }
+            }
+            }
+        ";
+
+        let mut warnings = lint(source, &Lints::default());
+        warnings.sort_by(|a, b| a.1.cmp(&b.1));
+        let kinds = warnings
+            .iter()
+            .map(|(kind, _)| kind.name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "magic_abort_codes",
+                "magic_abort_codes",
+                "missing_docs",
+                "unmoved_resources",
+                "unused_constants",
+                "unused_imports",
+                "unused_imports",
+                "unused_locals",
+            ]
+        );
+        let messages = warnings
+            .iter()
+            .map(|(_, message)| message.as_str())
+            .collect::<Vec<_>>();
+        assert!(messages.contains(&"Unused import 'Event'"));
+        assert!(messages.contains(&"Unused import 'emit'"));
+        assert!(messages.contains(&"Unused constant 'UNUSED'"));
+        assert!(messages.contains(&"Unused local variable 'unused'"));
+        assert!(messages.contains(&"Missing doc comment for the public function 'deposit'"));
+        assert!(
+            messages.contains(&"Resource 'Balance' is moved to the storage but never moved out")
+        );
+
+        let lints = Lints {
+            magic_abort_codes: LintLevel::Allow,
+            missing_docs: LintLevel::Deny,
+            ..Default::default()
+        };
+        let warnings = lint(source, &lints);
+        assert!(warnings
+            .iter()
+            .all(|(kind, _)| *kind != LintKind::MagicAbortCodes));
+        assert_eq!(warnings.len(), 6);
+    }
+
+    #[test]
+    fn test_spec_usages() {
+        let _pool = ConstPool::new();
+        let source = r"
+            module M {
+                use 0x1::Signer;
+
+                const MAX: u64 = 10;
+
+                /// Returns the value.
+                public fun get(account: &signer, value: u64): u64 {
+                    spec {
+                        assert value <= MAX;
+                    };
+                    value
+                }
+
+                spec fun get {
+                    aborts_if value > MAX;
+                    ensures Signer::spec_address_of(account) != 0x0;
+                }
+            }
+        ";
+        assert!(lint(source, &Lints::default()).is_empty());
+    }
+}
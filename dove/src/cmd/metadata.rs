@@ -12,12 +12,16 @@ fn into_metadata(ctx: Context) -> DoveMetadata {
         project_dir,
         manifest,
         dialect,
+        ..
     } = ctx;
-    let DoveToml { package, layout } = manifest;
+    let DoveToml {
+        package, layout, ..
+    } = manifest;
 
     let dependencies = package.dependencies.unwrap_or_default();
     let mut local_deps = vec![];
     let mut git_deps = vec![];
+    let mut binary_deps = vec![];
     for dep in dependencies.deps {
         match dep {
             Dependence::Git(git) => git_deps.push(git),
@@ -26,6 +30,11 @@ fn into_metadata(ctx: Context) -> DoveMetadata {
                     local_deps.push(abs_path.into_os_string().into_string().unwrap());
                 }
             }
+            Dependence::Binary(binary) => {
+                if let Ok(abs_path) = project_dir.join(binary.path).canonicalize() {
+                    binary_deps.push(abs_path.into_os_string().into_string().unwrap());
+                }
+            }
         }
     }
     let package_metadata = PackageMetadata {
@@ -35,6 +44,7 @@ fn into_metadata(ctx: Context) -> DoveMetadata {
         blockchain_api: package.blockchain_api,
         git_dependencies: git_deps,
         local_dependencies: local_deps,
+        binary_dependencies: binary_deps,
         dialect: dialect.name().to_string(),
     };
     DoveMetadata {
@@ -49,15 +59,35 @@ pub struct Metadata {}
 
 impl Cmd for Metadata {
     fn apply(self, ctx: Context) -> Result<(), Error> {
-        let metadata = into_metadata(ctx);
-        println!(
-            "{}",
-            serde_json::to_string_pretty::<DoveMetadata>(&metadata)?
-        );
+        if ctx.is_workspace() {
+            let members = ctx
+                .workspace_members()?
+                .into_iter()
+                .map(into_metadata)
+                .collect();
+            let metadata = WorkspaceMetadata { members };
+            println!(
+                "{}",
+                serde_json::to_string_pretty::<WorkspaceMetadata>(&metadata)?
+            );
+        } else {
+            let metadata = into_metadata(ctx);
+            println!(
+                "{}",
+                serde_json::to_string_pretty::<DoveMetadata>(&metadata)?
+            );
+        }
         Ok(())
     }
 }
 
+/// Workspace metadata.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMetadata {
+    /// Metadata of the workspace members in build order.
+    pub members: Vec<DoveMetadata>,
+}
+
 /// Movec manifest.
 #[derive(Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct DoveMetadata {
@@ -85,6 +115,8 @@ pub struct PackageMetadata {
     pub git_dependencies: Vec<Git>,
     /// Local dependency list.
     pub local_dependencies: Vec<String>,
+    /// Compiled dependency list.
+    pub binary_dependencies: Vec<String>,
     /// Dialect used in the project.
     pub dialect: String,
 }
@@ -103,7 +135,7 @@ mod tests {
         let move_project_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .join("resources")
             .join("test_move_project");
-        let context = get_context(move_project_dir.clone()).unwrap();
+        let context = get_context(move_project_dir.clone(), None).unwrap();
 
         let metadata = into_metadata(context);
 
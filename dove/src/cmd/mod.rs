@@ -10,30 +10,44 @@ use crate::context::{Context, get_context};
 
 /// Project builder.
 pub mod build;
+/// Type checker.
+pub mod check;
 /// Project dependencies loader.
 pub mod clean;
 /// Create transaction.
 pub mod ct;
+/// Documentation generator.
+pub mod doc;
 /// Dependencies fetcher.
 pub mod fetch;
+/// Source formatter.
+pub mod fmt;
 /// Project initializer.
 pub mod init;
+/// Project linter.
+pub mod lint;
 /// Project metadata.
 pub mod metadata;
 /// Project creator.
 pub mod new;
+/// Module package inspector.
+pub mod pac;
 /// Script executor.
 pub mod run;
 /// Test runner.
 pub mod test;
+/// Module dependency graph.
+pub mod tree;
+/// Dependencies updater.
+pub mod update;
 
 /// Move command.
 pub trait Cmd {
     /// Returns project context.
     /// This function must be overridden if the command is used with a custom context.
-    fn context(&self) -> Result<Context> {
+    fn context(&self, profile: Option<&str>) -> Result<Context> {
         let project_dir = env::current_dir()?;
-        get_context(project_dir)
+        get_context(project_dir, profile)
     }
 
     /// Apply command with given context.
@@ -41,12 +55,12 @@ pub trait Cmd {
     where
         Self: std::marker::Sized;
 
-    /// Functions create execution context and apply command with it.
-    fn execute(self) -> Result<()>
+    /// Functions create execution context with the given build profile and apply command with it.
+    fn execute(self, profile: Option<&str>) -> Result<()>
     where
         Self: std::marker::Sized,
     {
-        let context = self.context()?;
+        let context = self.context(profile)?;
         self.apply(context)
     }
 }
@@ -36,7 +36,7 @@ pub struct New {
 }
 
 impl Cmd for New {
-    fn context(&self) -> Result<Context, Error> {
+    fn context(&self, _profile: Option<&str>) -> Result<Context, Error> {
         create_context()
     }
 
@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Error;
+use diem::prelude::CompiledModule;
+use diem::vm::access::ModuleAccess;
+use structopt::StructOpt;
+
+use lang::disassembler::{Config, disasm_str};
+
+use crate::cache::sha3;
+use crate::cmd::Cmd;
+use crate::cmd::build::ModulePackage;
+use crate::index::meta::module_id;
+use crate::context::{Context, create_context};
+
+/// Inspect module package.
+#[derive(StructOpt, Debug)]
+pub struct Pac {
+    #[structopt(subcommand)]
+    cmd: PacCmd,
+}
+
+#[derive(StructOpt, Debug)]
+enum PacCmd {
+    #[structopt(about = "List package modules with their sizes and hashes")]
+    List {
+        #[structopt(help = "Path to the module package.")]
+        package: PathBuf,
+    },
+    #[structopt(about = "Extract package modules into .mv files")]
+    Extract {
+        #[structopt(help = "Path to the module package.")]
+        package: PathBuf,
+        #[structopt(
+            help = "Output directory. The directory next to the package with its name is used by default.",
+            short = "o",
+            long = "output"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[structopt(about = "Disassemble package modules")]
+    Disasm {
+        #[structopt(help = "Path to the module package.")]
+        package: PathBuf,
+        #[structopt(
+            help = "Output directory for the disassembled modules. Modules are printed if the directory is not specified.",
+            short = "o",
+            long = "output"
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+impl Cmd for Pac {
+    fn context(&self, _profile: Option<&str>) -> Result<Context, Error> {
+        create_context()
+    }
+
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        match self.cmd {
+            PacCmd::List { package } => {
+                let package = ctx.path_for(package);
+                println!("Package:[{}]", package.display());
+                for (idx, module) in load_package(&package)?.iter().enumerate() {
+                    println!(
+                        "{:>4} {:<48} {:>8} {}",
+                        idx,
+                        module.id,
+                        module.bytecode.len(),
+                        sha3(&module.bytecode)
+                    );
+                }
+            }
+            PacCmd::Extract { package, output } => {
+                let package = ctx.path_for(package);
+                let output = match output {
+                    Some(output) => ctx.path_for(output),
+                    None => package.with_extension(""),
+                };
+                fs::create_dir_all(&output)?;
+
+                for (idx, module) in load_package(&package)?.iter().enumerate() {
+                    let path = output.join(format!("{}_{}.mv", idx, module.name));
+                    fs::write(&path, &module.bytecode)?;
+                    println!("Store: {:?}", path.as_os_str());
+                }
+            }
+            PacCmd::Disasm { package, output } => {
+                let package = ctx.path_for(package);
+                let output = output.map(|output| ctx.path_for(output));
+                if let Some(output) = &output {
+                    fs::create_dir_all(output)?;
+                }
+
+                for (idx, module) in load_package(&package)?.iter().enumerate() {
+                    let source = disasm_str(
+                        &module.bytecode,
+                        Config {
+                            light_version: false,
+                        },
+                    )?;
+                    match &output {
+                        Some(output) => {
+                            let path = output.join(format!("{}_{}.move", idx, module.name));
+                            fs::write(&path, source)?;
+                            println!("Store: {:?}", path.as_os_str());
+                        }
+                        None => {
+                            println!("// {}", module.id);
+                            println!("{}", source);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Module from the package.
+struct PackageModule {
+    id: String,
+    name: String,
+    bytecode: Vec<u8>,
+}
+
+/// Reads modules of the package.
+fn load_package(path: &Path) -> Result<Vec<PackageModule>, Error> {
+    let bytes = fs::read(path).map_err(|err| anyhow!("Failed to read {:?}: {}", path, err))?;
+    let package = ModulePackage::decode(&bytes)
+        .map_err(|err| anyhow!("Failed to decode module package {:?}: {}", path, err))?;
+
+    package
+        .modules
+        .into_iter()
+        .enumerate()
+        .map(|(idx, bytecode)| {
+            let id = CompiledModule::deserialize(&bytecode)
+                .map_err(|err| anyhow!("Failed to deserialize module {}: {:?}", idx, err))?
+                .self_id();
+            Ok(PackageModule {
+                id: module_id(&id),
+                name: id.name().as_str().to_owned(),
+                bytecode,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cmd::build::ModulePackage;
+
+    #[test]
+    fn test_package_roundtrip() {
+        let package = ModulePackage {
+            modules: vec![vec![0xa1, 0x1c, 0xeb, 0x0b], vec![], vec![1, 2, 3]],
+        };
+        let decoded = ModulePackage::decode(&package.encode().unwrap()).unwrap();
+        assert_eq!(package.modules, decoded.modules);
+        assert!(ModulePackage::decode(&[0xff]).is_err());
+    }
+}
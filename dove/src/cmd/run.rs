@@ -2,6 +2,7 @@ use anyhow::Error;
 use structopt::StructOpt;
 
 use lang::compiler::file::{load_move_files, MoveFile};
+use lang::script_args::{parse_type_param, split_args};
 use move_executor::executor::{Executor, render_execution_result};
 
 use crate::cmd::{Cmd, load_dependencies};
@@ -15,12 +16,19 @@ pub struct Run {
     #[structopt(name = "Script signers.", long = "signers", short = "s")]
     signers: Vec<String>,
     #[structopt(
-        help = r#"Number of script main() function arguments in quotes, e.g. "10 20 30""#,
+        help = r#"Script main() function arguments, e.g. -a 10 "[20, 30]" or -a "10 [20, 30]""#,
         name = "Script args.",
         long = "args",
         short = "a"
     )]
     args: Vec<String>,
+    #[structopt(
+        help = r#"Script type parameters, e.g. 0x1::Dfinance::USD"#,
+        name = "Script type parameters.",
+        long = "type",
+        short = "t"
+    )]
+    type_parameters: Vec<String>,
 }
 
 impl Cmd for Run {
@@ -35,6 +43,7 @@ impl Cmd for Run {
         let mut index = ctx.build_index()?;
 
         let dep_set = index.make_dependency_set(&[&script, &module_dir])?;
+        let dep_senders = index.dep_senders(&dep_set)?;
         let mut dep_list = load_dependencies(dep_set)?;
         dep_list.extend(load_move_files(&[module_dir])?);
 
@@ -54,9 +63,25 @@ impl Cmd for Run {
             .map(|addr| ctx.dialect.normalize_account_address(addr))
             .unwrap_or_else(|| ctx.account_address())?;
 
-        let executor = Executor::new(ctx.dialect.as_ref(), sender, dep_list);
+        let executor = Executor::new(ctx.dialect.as_ref(), sender, dep_list)
+            .with_addresses(ctx.named_addresses()?)
+            .with_dep_senders(dep_senders);
         let script = MoveFile::load(script)?;
+        let type_args = self
+            .type_parameters
+            .iter()
+            .map(|tp| parse_type_param(tp))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Arguments are split the same way as the arguments of the executor.
+        let args = self
+            .args
+            .iter()
+            .map(|args| split_args(args))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect();
 
-        render_execution_result(executor.execute_script(script, Some(signers), self.args))
+        render_execution_result(executor.execute_script(script, Some(signers), args, type_args))
     }
 }
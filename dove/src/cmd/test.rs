@@ -1,9 +1,27 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::str::FromStr;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
 use crate::cmd::{Cmd, load_dependencies};
 use crate::context::Context;
+use crate::index::Index;
+use crate::watch::watch;
 use anyhow::Error;
+use diem::move_lang::errors::output_errors;
 use structopt::StructOpt;
-use lang::compiler::file::load_move_files;
-use move_executor::executor::{Executor, render_test_result};
+use termcolor::{ColorChoice, StandardStream};
+use lang::builder::{Artifacts, MoveBuilder};
+use lang::compiler::ConstPool;
+use lang::compiler::dialects::DialectName;
+use lang::compiler::error::CompilerError;
+use lang::compiler::file::{load_move_files, MoveFile};
+use move_executor::coverage::{CoverageReport, ExecutionTrace, Tracer};
+use move_executor::executor::{Executor, format_error, format_test_result};
+use move_executor::explain::StepExecutionResult;
+use move_executor::gas::{GAS_SNAPSHOT, GasSnapshot, TestGas, render_gas_table};
+use move_executor::report::{TestFormat, TestReport, TestResult, TestSuite};
 
 /// Run tests.
 #[derive(StructOpt, Debug)]
@@ -14,13 +32,242 @@ pub struct Test {
         help = "Specify test name to run (or substring)"
     )]
     name_pattern: Option<String>,
+    #[structopt(
+        help = "Rerun tests on changes of the source files.",
+        short = "w",
+        long = "watch"
+    )]
+    watch: bool,
+    #[structopt(
+        help = "Output format of the test results: text, json or junit.",
+        long = "format",
+        default_value = "text"
+    )]
+    format: TestFormat,
+    #[structopt(
+        help = "Number of threads to run tests on. \
+        Dependencies are compiled to bytecode once and shared by the threads, \
+        but every test script is still parsed and type checked together with the dependency sources.",
+        short = "j",
+        long = "jobs",
+        default_value = "1"
+    )]
+    jobs: usize,
+    #[structopt(
+        help = "Collect bytecode coverage of the project modules. \
+        Prints the coverage summary and writes the lcov report to target/coverage/lcov.info. \
+        Requires the debug build of dove.",
+        long = "coverage"
+    )]
+    coverage: bool,
+    #[structopt(
+        help = "Print gas usage of the tests and their steps.",
+        long = "gas-report"
+    )]
+    gas_report: bool,
+    #[structopt(
+        help = "Write gas usage of the passed tests to the .gas-snapshot file. \
+        With --name-pattern only the selected tests are updated in the snapshot.",
+        long = "gas-snapshot"
+    )]
+    gas_snapshot: bool,
+    #[structopt(
+        help = "Compare gas usage of the tests with the .gas-snapshot file. \
+        Fails if gas of a test rises by more than the tolerance.",
+        long = "check-gas-snapshot",
+        conflicts_with = "gas-snapshot"
+    )]
+    check_gas_snapshot: bool,
+    #[structopt(
+        help = "Allowed gas increase in percent for the snapshot check.",
+        long = "gas-tolerance",
+        default_value = "0"
+    )]
+    gas_tolerance: f64,
+}
+
+/// Output of the test.
+enum TestOutput {
+    /// Text output and `true` if the test failed.
+    Text(String, bool),
+    /// Test result of the report.
+    Report(TestResult),
+    /// Rendered compiler errors of the test script. Aborts the test run in the text format.
+    /// Report formats record compiler errors as failed tests.
+    CompileError(String),
 }
 
 impl Cmd for Test {
     fn apply(self, ctx: Context) -> Result<(), Error> {
+        let projects = ctx.projects()?;
+        let mut indexes = projects
+            .iter()
+            .map(|ctx| ctx.build_index())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tracer = if self.coverage {
+            let dir = ctx.path_for(&ctx.manifest.layout.target).join("coverage");
+            fs::create_dir_all(&dir)?;
+            Some(Arc::new(Tracer::new(&dir.join("trace"))?))
+        } else {
+            None
+        };
+
+        if self.watch {
+            return watch(&mut indexes, |indexes| {
+                self.run_all(indexes, tracer.as_ref())?;
+                Ok(())
+            });
+        }
+
+        if self.run_all(&mut indexes, tracer.as_ref())? {
+            Err(anyhow!("tests failed:{}", ctx.project_name()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Test {
+    /// Run tests of all projects.
+    /// Coverage of the project modules is reported if the tracer is given.
+    /// Returns `true` if any of the tests failed.
+    fn run_all(
+        &self,
+        indexes: &mut [Index],
+        tracer: Option<&Arc<Tracer>>,
+    ) -> Result<bool, Error> {
+        let mut report = TestReport::default();
+        let mut has_failures = false;
+        let mut gas_regressions = vec![];
+        for index in indexes.iter_mut() {
+            if index.ctx.workspace_dir.is_some() && self.format == TestFormat::Text {
+                println!("Test:[{}]", index.ctx.project_name());
+            }
+            let mut suite = TestSuite::new(&index.ctx.project_name());
+            let (failed, gas) = self.run_tests(index, &mut suite, tracer)?;
+            has_failures |= failed;
+            report.suites.push(suite);
+            if !self.gas(index.ctx, &gas)? {
+                gas_regressions.push(index.ctx.project_name());
+            }
+        }
+
+        if self.format != TestFormat::Text {
+            println!("{}", report.render(self.format)?);
+        }
+
+        if let Some(tracer) = tracer {
+            let trace = tracer.take();
+            for index in indexes {
+                self.coverage(index, &trace)?;
+            }
+        }
+
+        if !gas_regressions.is_empty() {
+            return Err(anyhow!(
+                "gas usage increased:{}",
+                gas_regressions.join(", ")
+            ));
+        }
+        Ok(has_failures)
+    }
+
+    /// Prints the gas report and writes or checks the gas snapshot of the project.
+    /// Returns `false` if gas of any test rose above the snapshot.
+    fn gas(&self, ctx: &Context, gas: &[TestGas]) -> Result<bool, Error> {
+        if self.gas_report && self.format == TestFormat::Text {
+            println!("Gas report:");
+            print!("{}", render_gas_table(gas));
+        }
+
+        let snapshot_path = ctx.path_for(GAS_SNAPSHOT);
+        if self.gas_snapshot {
+            // Filtered runs keep the snapshot of the other tests.
+            let mut snapshot = if self.name_pattern.is_some() && snapshot_path.exists() {
+                GasSnapshot::load(&snapshot_path)?
+            } else {
+                GasSnapshot::default()
+            };
+            snapshot.update(gas);
+            snapshot.store(&snapshot_path)?;
+        }
+
+        if self.check_gas_snapshot {
+            let regressions =
+                GasSnapshot::load(&snapshot_path)?.regressions(gas, self.gas_tolerance);
+            for regression in &regressions {
+                eprintln!(
+                    "{}: gas {} > {} (+{:.2}%)",
+                    regression.name,
+                    regression.actual,
+                    regression.expected,
+                    regression.percent()
+                );
+            }
+            return Ok(regressions.is_empty());
+        }
+        Ok(true)
+    }
+
+    /// Reports coverage of the project modules.
+    /// Prints the summary in the text format and writes the lcov report to the target directory.
+    fn coverage(&self, index: &mut Index, trace: &ExecutionTrace) -> Result<(), Error> {
+        let ctx = index.ctx;
+        let module_dir = ctx.path_for(&ctx.manifest.layout.module_dir);
+        if !module_dir.exists() {
+            return Ok(());
+        }
+
+        let dirs = [module_dir];
+        let dep_set = index.make_dependency_set(&dirs)?;
+        let dep_senders = index.dep_senders(&dep_set)?;
+        let dep_list = load_dependencies(dep_set)?;
+        let source_list = load_move_files(&dirs)?;
+
+        let sender = ctx.account_address()?;
+        let Artifacts { files, prog } = MoveBuilder::new(ctx.dialect.as_ref(), Some(&sender))
+            .with_addresses(ctx.named_addresses()?)
+            .with_dep_senders(dep_senders)
+            .build(&source_list, &dep_list);
+        let units = match prog {
+            Ok(units) => units,
+            Err(errors) => {
+                let mut writer = StandardStream::stderr(ColorChoice::Auto);
+                output_errors(&mut writer, files, errors);
+                return Err(anyhow!("could not compile:{}", ctx.project_name()));
+            }
+        };
+
+        let report = CoverageReport::new(&units, &files, trace);
+        if self.format == TestFormat::Text {
+            if ctx.workspace_dir.is_some() {
+                println!("Coverage:[{}]", ctx.project_name());
+            } else {
+                println!("Coverage:");
+            }
+            print!("{}", report.summary());
+        }
+
+        let dir = ctx.path_for(&ctx.manifest.layout.target).join("coverage");
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("lcov.info"), report.to_lcov())?;
+        Ok(())
+    }
+
+    /// Run project tests.
+    /// Results are added to the suite if the report format is used.
+    /// Returns `true` if any of the tests failed and gas usage of the passed tests.
+    fn run_tests(
+        &self,
+        index: &mut Index,
+        suite: &mut TestSuite,
+        tracer: Option<&Arc<Tracer>>,
+    ) -> Result<(bool, Vec<TestGas>), Error> {
+        let ctx = index.ctx;
         let tests_dir = ctx.path_for(&ctx.manifest.layout.tests_dir);
         if !tests_dir.exists() {
-            return Ok(());
+            return Ok((false, vec![]));
         }
 
         let mut dirs = ctx.paths_for(&[
@@ -30,16 +277,29 @@ impl Cmd for Test {
 
         dirs.push(tests_dir.clone());
 
-        let mut index = ctx.build_index()?;
-
         let dep_set = index.make_dependency_set(&dirs)?;
+        let dep_senders = index.dep_senders(&dep_set)?;
         let mut dep_list = load_dependencies(dep_set)?;
 
         dep_list.extend(load_move_files(&dirs[..dirs.len() - 1])?);
 
-        let executor = Executor::new(ctx.dialect.as_ref(), ctx.account_address()?, dep_list);
+        let sender = ctx.account_address()?;
+        let addresses = ctx.named_addresses()?;
+        let executor = Executor::new(ctx.dialect.as_ref(), sender.clone(), dep_list.clone())
+            .with_addresses(addresses.clone())
+            .with_dep_senders(dep_senders.clone())
+            .with_tracer(tracer.cloned())
+            .compile_deps()
+            .map_err(|err| match err.downcast::<CompilerError>() {
+                Ok(error) => {
+                    let mut writer = StandardStream::stderr(ColorChoice::Auto);
+                    output_errors(&mut writer, error.source_map, error.errors);
+                    anyhow!("could not compile:{}", ctx.project_name())
+                }
+                Err(err) => err,
+            })?;
 
-        let mut has_failures = false;
+        let mut tests = vec![];
         for test in load_move_files(&[tests_dir])? {
             let test_name = Executor::script_name(&test)?;
 
@@ -48,18 +308,127 @@ impl Cmd for Test {
                     continue;
                 }
             }
+            tests.push((test_name, test));
+        }
 
-            let is_err =
-                render_test_result(&test_name, executor.execute_script(test, None, vec![]))?;
-            if is_err {
-                has_failures = true;
+        let mut has_failures = false;
+        let mut gas = vec![];
+        let mut handle = |(output, test_gas): (TestOutput, Option<TestGas>)| {
+            match output {
+                TestOutput::Text(output, is_err) => {
+                    print!("{}", output);
+                    has_failures |= is_err;
+                }
+                TestOutput::Report(result) => {
+                    has_failures |= result.is_failed();
+                    suite.tests.push(result);
+                }
+                TestOutput::CompileError(errors) => {
+                    eprint!("{}", errors);
+                    return Err(anyhow!("could not compile:{}", ctx.project_name()));
+                }
             }
+            gas.extend(test_gas);
+            Ok(())
+        };
+
+        if self.jobs <= 1 || tests.len() <= 1 {
+            for (test_name, test) in tests {
+                handle(run_test(&executor, self.format, &test_name, test))?;
+            }
+            return Ok((has_failures, gas));
         }
 
-        if has_failures {
-            Err(anyhow!("tests failed:{}", ctx.project_name()))
-        } else {
-            Ok(())
+        let compiled_deps = executor.compiled_deps().cloned().unwrap_or_default();
+        let queue = Arc::new(Mutex::new(
+            tests.into_iter().enumerate().collect::<VecDeque<_>>(),
+        ));
+        let (tx, rx) = mpsc::channel();
+        let mut workers = vec![];
+        for _ in 0..self.jobs.min(queue.lock().unwrap().len()) {
+            let queue = queue.clone();
+            let tx = tx.clone();
+            let dialect = ctx.dialect.name().to_owned();
+            let sender = sender.clone();
+            let addresses = addresses.clone();
+            let dep_senders = dep_senders.clone();
+            let dep_list = dep_list.clone();
+            let compiled_deps = compiled_deps.clone();
+            let tracer = tracer.cloned();
+            let format = self.format;
+            workers.push(thread::spawn(move || -> Result<(), Error> {
+                let _pool = ConstPool::new();
+                let dialect = DialectName::from_str(&dialect)?.get_dialect();
+                let executor = Executor::new(dialect.as_ref(), sender, dep_list)
+                    .with_addresses(addresses)
+                    .with_dep_senders(dep_senders)
+                    .with_compiled_deps(compiled_deps)
+                    .with_tracer(tracer);
+                loop {
+                    let next = queue.lock().unwrap().pop_front();
+                    let (idx, (test_name, test)) = match next {
+                        Some(next) => next,
+                        None => return Ok(()),
+                    };
+                    let output = run_test(&executor, format, &test_name, test);
+                    if tx.send((idx, output)).is_err() {
+                        return Ok(());
+                    }
+                }
+            }));
+        }
+        drop(tx);
+
+        // Outputs are handled in the order of the tests.
+        // Returning on a compiler error drops the receiver, so the workers stop.
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        for (idx, output) in rx {
+            pending.insert(idx, output);
+            while let Some(output) = pending.remove(&next) {
+                handle(output)?;
+                next += 1;
+            }
         }
+        for worker in workers {
+            worker
+                .join()
+                .map_err(|_| anyhow!("Test thread panicked"))??;
+        }
+
+        Ok((has_failures, gas))
     }
 }
+
+/// Runs the test and returns its output in the given format.
+/// Gas usage is returned if the test passed.
+/// Compiler errors are rendered on the thread of the test, as their sources are kept in its pool.
+fn run_test(
+    executor: &Executor,
+    format: TestFormat,
+    test_name: &str,
+    test: MoveFile,
+) -> (TestOutput, Option<TestGas>) {
+    let result = executor.execute_script(test, None, vec![], vec![]);
+    let result = match result {
+        Err(err) if format == TestFormat::Text && err.is::<CompilerError>() => {
+            return (TestOutput::CompileError(format_error(err)), None);
+        }
+        result => result,
+    };
+    let gas = result
+        .as_ref()
+        .ok()
+        .filter(|result| match result.step_results.last() {
+            Some((_, _, _, step)) => !matches!(step, StepExecutionResult::Error(_)),
+            None => false,
+        })
+        .map(|result| TestGas::new(test_name, result));
+    let output = if format == TestFormat::Text {
+        let (output, is_err) = format_test_result(test_name, result);
+        TestOutput::Text(output, is_err)
+    } else {
+        TestOutput::Report(TestResult::new(test_name, result))
+    };
+    (output, gas)
+}
@@ -0,0 +1,444 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use anyhow::Error;
+use serde::Serialize;
+use structopt::StructOpt;
+
+use crate::cmd::Cmd;
+use crate::index::meta::{module_id, parse_module_id};
+use crate::context::Context;
+use crate::index::meta::source_meta;
+use crate::index::move_dir_iter;
+
+/// Print module dependency graph.
+#[derive(StructOpt, Debug)]
+pub struct Tree {
+    #[structopt(
+        help = "Show reverse dependencies of the module. Example: 0x1::Coins",
+        long = "invert",
+        short = "i"
+    )]
+    invert: Option<String>,
+    #[structopt(
+        help = "Output format: text, dot or json.",
+        long = "format",
+        default_value = "text"
+    )]
+    format: Format,
+}
+
+/// Output format of the dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Dot,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "dot" => Ok(Format::Dot),
+            "json" => Ok(Format::Json),
+            _ => Err(anyhow!(
+                "Invalid format {:?}. Expected text, dot or json.",
+                s
+            )),
+        }
+    }
+}
+
+impl Cmd for Tree {
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        let graph = make_graph(&ctx)?;
+
+        let graph = match &self.invert {
+            Some(module) => {
+                let id = module_id(&parse_module_id(&ctx, module)?);
+                if !graph.nodes.contains_key(&id) {
+                    return Err(anyhow!("Module {} not found in the dependency graph", id));
+                }
+                graph.invert(&id)
+            }
+            None => graph,
+        };
+
+        match self.format {
+            Format::Text => print!("{}", graph.to_text()),
+            Format::Dot => print!("{}", graph.to_dot()),
+            Format::Json => println!("{}", serde_json::to_string_pretty(&graph)?),
+        }
+        Ok(())
+    }
+}
+
+/// Module dependency graph.
+#[derive(Serialize, Debug, Default)]
+pub struct Graph {
+    /// Graph nodes by module identifier.
+    pub nodes: BTreeMap<String, Node>,
+    /// Identifiers of the nodes the graph is printed from.
+    pub roots: Vec<String>,
+}
+
+/// Module node of the dependency graph.
+#[derive(Serialize, Debug, Clone)]
+pub struct Node {
+    /// Module source: `Project`, `Script`, `Test`, `Unresolved`
+    /// or the `SourceType` of the dependency.
+    pub source: String,
+    /// Name of the dependency or the workspace member the module comes from.
+    pub dep_name: Option<String>,
+    /// Path to the module source.
+    pub path: Option<String>,
+    /// Edges of the node: module imports or dependents if the graph is inverted.
+    pub edges: BTreeSet<String>,
+}
+
+impl Graph {
+    /// Returns the graph of the modules depending on the given module.
+    fn invert(&self, id: &str) -> Graph {
+        let mut nodes: BTreeMap<String, Node> = self
+            .nodes
+            .iter()
+            .map(|(id, node)| {
+                let mut node = node.clone();
+                node.edges.clear();
+                (id.to_owned(), node)
+            })
+            .collect();
+        for (id, node) in &self.nodes {
+            for dep in &node.edges {
+                if let Some(dep) = nodes.get_mut(dep) {
+                    dep.edges.insert(id.to_owned());
+                }
+            }
+        }
+
+        let mut graph = Graph {
+            nodes: Default::default(),
+            roots: vec![id.to_owned()],
+        };
+        let mut queue = vec![id.to_owned()];
+        while let Some(id) = queue.pop() {
+            if graph.nodes.contains_key(&id) {
+                continue;
+            }
+            if let Some(node) = nodes.remove(&id) {
+                queue.extend(node.edges.iter().cloned());
+                graph.nodes.insert(id, node);
+            }
+        }
+        graph
+    }
+
+    /// Sets nodes without incoming edges as roots.
+    /// Cycles unreachable from these roots are printed from their first node.
+    fn find_roots(&mut self) {
+        let referenced = self
+            .nodes
+            .values()
+            .flat_map(|node| node.edges.iter().map(|edge| edge.as_str()))
+            .collect::<BTreeSet<_>>();
+        let mut roots = self
+            .nodes
+            .keys()
+            .map(|id| id.as_str())
+            .filter(|id| !referenced.contains(id))
+            .collect::<Vec<_>>();
+
+        let mut reached = BTreeSet::new();
+        for root in &roots {
+            self.reach(*root, &mut reached);
+        }
+        for id in self.nodes.keys() {
+            if !reached.contains(id.as_str()) {
+                roots.push(id);
+                self.reach(id, &mut reached);
+            }
+        }
+        let roots = roots.into_iter().map(ToOwned::to_owned).collect();
+        self.roots = roots;
+    }
+
+    /// Adds the nodes reachable from the given node to `reached`.
+    fn reach<'b>(&'b self, id: &'b str, reached: &mut BTreeSet<&'b str>) {
+        let mut queue = vec![id];
+        while let Some(id) = queue.pop() {
+            if !reached.insert(id) {
+                continue;
+            }
+            if let Some(node) = self.nodes.get(id) {
+                queue.extend(node.edges.iter().map(|edge| edge.as_str()));
+            }
+        }
+    }
+
+    /// Renders the graph as a tree.
+    /// Subtrees already printed are marked with `(*)`.
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        let mut printed = BTreeSet::new();
+        for root in &self.roots {
+            self.write_node(&mut out, root, "", None, &mut vec![], &mut printed);
+        }
+        out
+    }
+
+    fn write_node<'b>(
+        &'b self,
+        out: &mut String,
+        id: &'b str,
+        prefix: &str,
+        is_last: Option<bool>,
+        path: &mut Vec<&'b str>,
+        printed: &mut BTreeSet<&'b str>,
+    ) {
+        let (branch, child_prefix) = match is_last {
+            None => ("", prefix.to_owned()),
+            Some(true) => ("└── ", format!("{}    ", prefix)),
+            Some(false) => ("├── ", format!("{}│   ", prefix)),
+        };
+        out.push_str(prefix);
+        out.push_str(branch);
+        out.push_str(id);
+
+        let node = match self.nodes.get(id) {
+            Some(node) => node,
+            None => {
+                out.push('\n');
+                return;
+            }
+        };
+        out.push_str(&format!(" ({}", node.source));
+        if let Some(dep_name) = &node.dep_name {
+            out.push_str(&format!(", {}", dep_name));
+        }
+        out.push(')');
+
+        if path.contains(&id) {
+            out.push_str(" (cycle)\n");
+            return;
+        }
+        if !node.edges.is_empty() && !printed.insert(id) {
+            out.push_str(" (*)\n");
+            return;
+        }
+        out.push('\n');
+
+        path.push(id);
+        let count = node.edges.len();
+        for (idx, edge) in node.edges.iter().enumerate() {
+            self.write_node(
+                out,
+                edge,
+                &child_prefix,
+                Some(idx + 1 == count),
+                path,
+                printed,
+            );
+        }
+        path.pop();
+    }
+
+    /// Renders the graph in the graphviz dot format.
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+        for (id, node) in &self.nodes {
+            let label = match &node.dep_name {
+                Some(dep_name) => format!("{}\\n{} {}", id, node.source, dep_name),
+                None => format!("{}\\n{}", id, node.source),
+            };
+            out.push_str(&format!("    \"{}\" [label=\"{}\"];\n", id, label));
+        }
+        for (id, node) in &self.nodes {
+            for edge in &node.edges {
+                out.push_str(&format!("    \"{}\" -> \"{}\";\n", id, edge));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Builds the dependency graph of the project sources and the dependencies they import.
+/// Workspace members are added to the same graph.
+fn make_graph(ctx: &Context) -> Result<Graph, Error> {
+    let mut graph = Graph::default();
+    for project in ctx.projects()?.iter() {
+        add_project(&mut graph, ctx, project)?;
+    }
+
+    let unresolved = graph
+        .nodes
+        .values()
+        .flat_map(|node| node.edges.iter())
+        .filter(|id| !graph.nodes.contains_key(*id))
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    for id in unresolved {
+        graph.nodes.insert(
+            id,
+            Node {
+                source: "Unresolved".to_owned(),
+                dep_name: None,
+                path: None,
+                edges: Default::default(),
+            },
+        );
+    }
+
+    graph.find_roots();
+    Ok(graph)
+}
+
+/// Adds modules, scripts and tests of the project and the dependency modules they reach.
+/// Scripts are identified by their file path and function name.
+fn add_project(graph: &mut Graph, root: &Context, ctx: &Context) -> Result<(), Error> {
+    let layout = &ctx.manifest.layout;
+    let dirs = [
+        (ctx.path_for(&layout.module_dir), "Project"),
+        (ctx.path_for(&layout.script_dir), "Script"),
+        (ctx.path_for(&layout.tests_dir), "Test"),
+    ]
+    .iter()
+    .filter(|(dir, _)| dir.exists())
+    .cloned()
+    .collect::<Vec<_>>();
+
+    let mut index = ctx.build_index()?;
+    let paths = dirs.iter().map(|(dir, _)| dir).collect::<Vec<_>>();
+    let dep_set = index.make_dependency_set(&paths)?;
+    for id in index.modules.keys() {
+        let module = match index.get_module(id) {
+            Some(module) if dep_set.contains(&module.path) => module,
+            _ => continue,
+        };
+        // Sources of the workspace members take precedence over their copies in dependencies.
+        graph.nodes.entry(module_id(id)).or_insert_with(|| Node {
+            source: format!("{:?}", module.source_type),
+            dep_name: Some(module.dep_name.to_string()),
+            path: Some(module.path.to_string()),
+            edges: module.dependencies.iter().map(|id| module_id(id)).collect(),
+        });
+    }
+
+    let member = if root.is_workspace() {
+        Some(ctx.project_name())
+    } else {
+        None
+    };
+    let addresses = ctx.named_addresses()?;
+    let sender = ctx.account_address()?.as_account_address();
+    for (dir, source) in &dirs {
+        for file in move_dir_iter(dir) {
+            let meta = source_meta(file.path(), Some(sender), ctx.dialect.as_ref(), &addresses)?;
+            for unit in meta.meta {
+                let (id, source) = match &unit.script {
+                    Some(name) => {
+                        let path = file.path();
+                        let path = path.strip_prefix(&root.project_dir).unwrap_or(path);
+                        (format!("{}::{}", path.display(), name), *source)
+                    }
+                    None => (module_id(&unit.module_id), "Project"),
+                };
+                graph.nodes.insert(
+                    id,
+                    Node {
+                        source: source.to_owned(),
+                        dep_name: member.clone(),
+                        path: Some(meta.path.to_string()),
+                        edges: unit.imports.iter().map(|id| module_id(id)).collect(),
+                    },
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::cmd::tree::{Graph, Node};
+
+    fn node(edges: &[&str]) -> Node {
+        Node {
+            source: "Project".to_owned(),
+            dep_name: None,
+            path: None,
+            edges: edges.iter().map(|edge| edge.to_string()).collect(),
+        }
+    }
+
+    fn graph() -> Graph {
+        let mut graph = Graph::default();
+        graph
+            .nodes
+            .insert("0x1::A".to_owned(), node(&["0x1::B", "0x1::C"]));
+        graph.nodes.insert("0x1::B".to_owned(), node(&["0x1::C"]));
+        graph.nodes.insert("0x1::C".to_owned(), node(&[]));
+        graph.nodes.insert("0x1::D".to_owned(), node(&["0x1::B"]));
+        graph.find_roots();
+        graph
+    }
+
+    #[test]
+    fn test_tree() {
+        let graph = graph();
+        assert_eq!(graph.roots, vec!["0x1::A", "0x1::D"]);
+        assert_eq!(
+            graph.to_text(),
+            "0x1::A (Project)\n\
+             ├── 0x1::B (Project)\n\
+             │   └── 0x1::C (Project)\n\
+             └── 0x1::C (Project)\n\
+             0x1::D (Project)\n\
+             └── 0x1::B (Project) (*)\n"
+        );
+    }
+
+    #[test]
+    fn test_cycle_roots() {
+        let mut graph = Graph::default();
+        graph.nodes.insert("0x1::A".to_owned(), node(&["0x1::B"]));
+        graph.nodes.insert("0x1::B".to_owned(), node(&["0x1::A"]));
+        graph.nodes.insert("0x1::C".to_owned(), node(&["0x1::D"]));
+        graph.nodes.insert("0x1::D".to_owned(), node(&["0x1::C"]));
+        graph.nodes.insert("0x1::E".to_owned(), node(&["0x1::D"]));
+        graph.find_roots();
+        assert_eq!(graph.roots, vec!["0x1::E", "0x1::A"]);
+        assert_eq!(
+            graph.to_text(),
+            "0x1::E (Project)\n\
+             └── 0x1::D (Project)\n\
+             \u{20}   └── 0x1::C (Project)\n\
+             \u{20}       └── 0x1::D (Project) (cycle)\n\
+             0x1::A (Project)\n\
+             └── 0x1::B (Project)\n\
+             \u{20}   └── 0x1::A (Project) (cycle)\n"
+        );
+    }
+
+    #[test]
+    fn test_invert() {
+        let inverted = graph().invert("0x1::B");
+        assert_eq!(inverted.roots, vec!["0x1::B"]);
+        assert_eq!(
+            inverted.nodes.keys().collect::<Vec<_>>(),
+            vec!["0x1::A", "0x1::B", "0x1::D"]
+        );
+        assert_eq!(
+            inverted.nodes["0x1::B"].edges,
+            ["0x1::A", "0x1::D"]
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<BTreeSet<_>>()
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::Error;
+use structopt::StructOpt;
+
+use crate::cmd::Cmd;
+use crate::context::Context;
+use crate::index::resolver::git;
+use crate::lock::DoveLock;
+
+/// Update git dependencies.
+#[derive(StructOpt, Debug)]
+pub struct Update {
+    #[structopt(help = "Repository name or git url of the dependency to update. \
+        All git dependencies are updated if the name is not specified.")]
+    dep_name: Option<String>,
+}
+
+impl Cmd for Update {
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        // Make sure that all dependencies are checked out and locked.
+        ctx.build_index()?;
+
+        let mut lock = DoveLock::load(&ctx)?;
+        let names = lock
+            .git
+            .iter()
+            .filter(|dep| match &self.dep_name {
+                Some(name) => dep.git == *name || dep.repo_name() == name,
+                None => true,
+            })
+            .map(|dep| dep.name.clone())
+            .collect::<Vec<_>>();
+
+        if let Some(dep_name) = &self.dep_name {
+            if names.is_empty() {
+                return Err(anyhow!("git dependency '{}' not found", dep_name));
+            }
+        }
+
+        for name in names {
+            let dep = lock
+                .get_mut(&name)
+                .ok_or_else(|| anyhow!("git dependency '{}' not found", name))?;
+            if dep.is_pinned() {
+                println!("Pinned:[{}] {}", dep.git, dep.commit);
+                continue;
+            }
+
+            let commit = git::update(&ctx, dep)?.to_string();
+            if commit == dep.commit {
+                println!("Up to date:[{}] {}", dep.git, dep.commit);
+            } else {
+                println!("Update:[{}] {} -> {}", dep.git, dep.commit, commit);
+                dep.commit = commit;
+            }
+        }
+
+        lock.store(&ctx)?;
+        ctx.build_index()?;
+        Ok(())
+    }
+}
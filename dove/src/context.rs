@@ -1,11 +1,13 @@
 use std::path::{PathBuf, Path};
-use crate::manifest::{DoveToml, MANIFEST, read_manifest, default_dialect};
+use crate::manifest::{DoveToml, MANIFEST, read_manifest, default_dialect, Dependence};
+use crate::lock::LOCK_FILE;
 use std::str::FromStr;
 use anyhow::{Result, anyhow, Error};
 use std::env;
 use lang::compiler::dialects::{Dialect, DialectName};
-use lang::compiler::address::ProvidedAccountAddress;
+use lang::compiler::address::{ProvidedAccountAddress, NamedAddresses};
 use crate::index::Index;
+use lang::compiler::file::find_move_files;
 
 /// Project context.
 pub struct Context {
@@ -15,6 +17,10 @@ pub struct Context {
     pub manifest: DoveToml,
     /// Move dialect.
     pub dialect: Box<dyn Dialect>,
+    /// Workspace root directory if the project is a workspace member.
+    pub workspace_dir: Option<PathBuf>,
+    /// Selected build profile.
+    pub profile: Option<String>,
 }
 
 impl Context {
@@ -32,6 +38,112 @@ impl Context {
             .collect()
     }
 
+    /// Returns absolute paths of the local dependencies.
+    pub fn path_deps(&self) -> Vec<PathBuf> {
+        self.manifest
+            .package
+            .dependencies
+            .iter()
+            .flat_map(|deps| deps.deps.iter())
+            .filter_map(|dep| match dep {
+                Dependence::Path(path) => Some(self.path_for(&path.path)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns path to the lock file.
+    /// Workspace members share the lock file of the workspace root.
+    pub fn lock_path(&self) -> PathBuf {
+        self.workspace_dir
+            .as_ref()
+            .unwrap_or(&self.project_dir)
+            .join(LOCK_FILE)
+    }
+
+    /// Returns `true` if the project is a workspace.
+    pub fn is_workspace(&self) -> bool {
+        self.manifest.workspace.is_some()
+    }
+
+    /// Returns the projects a command runs on:
+    /// the workspace members or the project itself if it is not a workspace.
+    pub fn projects(&self) -> Result<Projects> {
+        Ok(Projects {
+            root: self,
+            members: self.workspace_members()?,
+        })
+    }
+
+    /// Returns contexts of the workspace members.
+    /// Members share the dependencies cache and the index of the workspace
+    /// and are ordered so that every member follows its path dependencies.
+    pub fn workspace_members(&self) -> Result<Vec<Context>> {
+        let workspace = match &self.manifest.workspace {
+            Some(workspace) => workspace,
+            None => return Ok(vec![]),
+        };
+        self.check_workspace_root()?;
+
+        let target_deps = path_to_string(&self.path_for(&self.manifest.layout.target_deps))?;
+        let index = path_to_string(&self.path_for(&self.manifest.layout.index))?;
+
+        let members = workspace
+            .members
+            .iter()
+            .map(|member| {
+                let mut ctx = get_context(self.path_for(member), None)?;
+                if let Some(profile) = &self.profile {
+                    // Members inherit the profiles of the workspace root.
+                    for (name, root_profile) in &self.manifest.profile {
+                        ctx.manifest
+                            .profile
+                            .entry(name.to_owned())
+                            .or_insert_with(|| root_profile.clone());
+                    }
+                    ctx.manifest.apply_profile(profile)?;
+                    ctx.profile = Some(profile.to_owned());
+                }
+                ctx.manifest.layout.target_deps = target_deps.clone();
+                ctx.manifest.layout.index = index.clone();
+                ctx.workspace_dir = Some(self.project_dir.clone());
+                Ok(ctx)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        sort_members(members)
+    }
+
+    /// Rejects a workspace root with its own package sources or dependencies.
+    /// Commands only process the workspace members, so the root sources would be ignored.
+    fn check_workspace_root(&self) -> Result<()> {
+        let has_deps = self
+            .manifest
+            .package
+            .dependencies
+            .as_ref()
+            .map(|deps| !deps.deps.is_empty())
+            .unwrap_or_default();
+        let layout = &self.manifest.layout;
+        let has_sources = self
+            .paths_for(&[&layout.module_dir, &layout.script_dir, &layout.tests_dir])
+            .iter()
+            .map(find_move_files)
+            .collect::<Result<Vec<_>>>()?
+            .iter()
+            .any(|files| !files.is_empty());
+
+        if has_deps || has_sources {
+            Err(anyhow!(
+                "workspace root `{:?}` has its own package sources or dependencies. \
+                 Move them to a workspace member.",
+                self.project_dir
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Build project index.
     pub fn build_index(&self) -> Result<Index, Error> {
         let mut index = Index::load(self)?;
@@ -61,6 +173,37 @@ impl Context {
 
         self.dialect.normalize_account_address(&acc_addr)
     }
+
+    /// Returns named addresses normalized through the project dialect.
+    pub fn named_addresses(&self) -> Result<NamedAddresses> {
+        let mut addresses = NamedAddresses::default();
+        for (name, addr) in &self.manifest.addresses {
+            let addr = self
+                .dialect
+                .normalize_account_address(addr)
+                .map_err(|err| anyhow!("invalid named address '{}': {:?}", name, err))?;
+            addresses.insert(name.to_owned(), addr);
+        }
+        Ok(addresses)
+    }
+}
+
+/// Projects of the command: the workspace members or the single project.
+pub struct Projects<'a> {
+    root: &'a Context,
+    members: Vec<Context>,
+}
+
+impl<'a> Projects<'a> {
+    /// Returns an iterator over the project contexts.
+    pub fn iter(&self) -> impl Iterator<Item = &Context> + '_ {
+        let root = if self.root.is_workspace() {
+            None
+        } else {
+            Some(self.root)
+        };
+        root.into_iter().chain(self.members.iter())
+    }
 }
 
 /// Create a new context for the current directory.
@@ -79,12 +222,20 @@ pub fn create_context() -> Result<Context> {
         project_dir,
         manifest,
         dialect: dialect.get_dialect(),
+        workspace_dir: None,
+        profile: None,
     })
 }
 
-/// Returns project context.
-pub fn get_context(project_dir: PathBuf) -> Result<Context> {
-    let manifest = load_manifest(&project_dir)?;
+/// Returns project context with the given build profile applied.
+/// The profile of a workspace is applied to each of its members.
+pub fn get_context(project_dir: PathBuf, profile: Option<&str>) -> Result<Context> {
+    let mut manifest = load_manifest(&project_dir)?;
+    if let Some(profile) = profile {
+        if manifest.workspace.is_none() {
+            manifest.apply_profile(profile)?;
+        }
+    }
 
     let dialect_name = manifest
         .package
@@ -97,6 +248,8 @@ pub fn get_context(project_dir: PathBuf) -> Result<Context> {
         project_dir,
         manifest,
         dialect: dialect.get_dialect(),
+        workspace_dir: None,
+        profile: profile.map(|profile| profile.to_owned()),
     })
 }
 
@@ -112,3 +265,60 @@ fn load_manifest(project_dir: &Path) -> Result<DoveToml> {
         read_manifest(&manifest)
     }
 }
+
+fn path_to_string(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(|path| path.to_owned())
+        .ok_or_else(|| anyhow!("Failed to convert path:{:?}", path))
+}
+
+/// Orders workspace members topologically by their path dependencies.
+fn sort_members(members: Vec<Context>) -> Result<Vec<Context>> {
+    let dirs = members
+        .iter()
+        .map(|member| member.project_dir.canonicalize())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let deps = members
+        .iter()
+        .enumerate()
+        .map(|(idx, member)| {
+            member
+                .path_deps()
+                .into_iter()
+                .filter_map(|path| path.canonicalize().ok())
+                .filter_map(|path| {
+                    dirs.iter()
+                        .enumerate()
+                        .find(|(dep_idx, dir)| *dep_idx != idx && path.starts_with(dir))
+                        .map(|(dep_idx, _)| dep_idx)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut done = vec![false; members.len()];
+    let mut order = Vec::with_capacity(members.len());
+    while order.len() < members.len() {
+        let next = (0..members.len())
+            .find(|idx| !done[*idx] && deps[*idx].iter().all(|dep| done[*dep]))
+            .ok_or_else(|| {
+                let cycle = (0..members.len())
+                    .filter(|idx| !done[*idx])
+                    .map(|idx| members[idx].project_name())
+                    .collect::<Vec<_>>();
+                anyhow!(
+                    "cyclic path dependencies between workspace members: [{}]",
+                    cycle.join(", ")
+                )
+            })?;
+        done[next] = true;
+        order.push(next);
+    }
+
+    let mut members = members.into_iter().map(Some).collect::<Vec<_>>();
+    Ok(order
+        .into_iter()
+        .filter_map(|idx| members[idx].take())
+        .collect())
+}
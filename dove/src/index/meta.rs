@@ -2,6 +2,7 @@ use anyhow::{Result, Error};
 use std::path::Path;
 use lang::compiler::parser::parse_file;
 use diem::{prelude::*};
+use diem::vm::file_format::{CompiledScript, ModuleHandle};
 use diem::{
     move_lang::{errors, parser::ast::*, name_pool::ConstPool},
 };
@@ -9,15 +10,18 @@ use diem::{
 use std::collections::{HashSet, HashMap};
 use termcolor::{StandardStream, ColorChoice};
 use lang::compiler::dialects::Dialect;
+use lang::compiler::address::NamedAddresses;
 use std::fs;
 use rand::random;
 use std::rc::Rc;
+use crate::context::Context;
 
 /// Extracts metadata form source code.
 pub fn source_meta(
     file: &Path,
     address: Option<AccountAddress>,
     dialect: &dyn Dialect,
+    addresses: &NamedAddresses,
 ) -> Result<FileMeta, Error> {
     let name = ConstPool::push(file.to_str().unwrap_or("source"));
     let source = fs::read_to_string(file)?;
@@ -33,6 +37,7 @@ pub fn source_meta(
         name,
         &source,
         sender.as_ref(),
+        addresses,
     );
     if errors.is_empty() {
         let mut metadata = Vec::new();
@@ -66,6 +71,8 @@ pub fn source_meta(
 pub struct DefinitionMeta {
     /// Module identifier.
     pub module_id: ModuleId,
+    /// Name of the script function if the definition is a script.
+    pub script: Option<String>,
     /// Module imports.
     pub imports: HashSet<Rc<ModuleId>>,
 }
@@ -107,6 +114,7 @@ impl DefinitionMeta {
     fn module(module: &ModuleDefinition, address: AccountAddress) -> Result<DefinitionMeta> {
         let mut meta = DefinitionMeta {
             module_id: ModuleId::new(address, Identifier::new(module.name.0.value.to_owned())?),
+            script: None,
             imports: Default::default(),
         };
 
@@ -439,6 +447,7 @@ impl DefinitionMeta {
                     random::<u32>()
                 ))?,
             ),
+            script: Some(script.function.name.0.value.to_owned()),
             imports: Default::default(),
         };
 
@@ -450,6 +459,26 @@ impl DefinitionMeta {
     }
 }
 
+/// Returns fully qualified module id.
+pub fn module_id(id: &ModuleId) -> String {
+    format!("0x{}::{}", id.address(), id.name())
+}
+
+/// Parses module identifier in the `<address>::<name>` format.
+pub fn parse_module_id(ctx: &Context, module: &str) -> Result<ModuleId, Error> {
+    let idx = module.rfind("::").ok_or_else(|| {
+        anyhow!(
+            "Expected module identifier <address>::<name>, got {}",
+            module
+        )
+    })?;
+    let address = ctx
+        .dialect
+        .normalize_account_address(&module[..idx])?
+        .as_account_address();
+    Ok(ModuleId::new(address, Identifier::new(&module[idx + 2..])?))
+}
+
 /// Extract dependencies from bytecode.
 pub fn extract_bytecode_dependencies(bytecode: &[u8]) -> Result<HashSet<ModuleId>> {
     let mut extractor = BytecodeUses::default();
@@ -460,6 +489,16 @@ pub fn extract_bytecode_dependencies(bytecode: &[u8]) -> Result<HashSet<ModuleId
     Ok(extractor.imports())
 }
 
+/// Extract dependencies from script bytecode.
+pub fn extract_script_dependencies(bytecode: &[u8]) -> Result<HashSet<ModuleId>> {
+    let mut extractor = BytecodeUses::default();
+    extractor.extract_script(
+        CompiledScript::deserialize(bytecode)
+            .map_err(|e| e.finish(Location::Undefined).into_vm_status())?,
+    )?;
+    Ok(extractor.imports())
+}
+
 /// Bytecode dependencies extractor.
 #[derive(Default)]
 pub struct BytecodeUses {
@@ -481,11 +520,34 @@ impl BytecodeUses {
             module_handles.remove(0);
         }
 
+        self.extract_handles(
+            &module_handles,
+            &module.identifiers,
+            &module.address_identifiers,
+        )
+    }
+
+    /// Extracts dependencies from compiled script.
+    pub fn extract_script(&mut self, script: CompiledScript) -> Result<()> {
+        let script = script.into_inner();
+        self.extract_handles(
+            &script.module_handles,
+            &script.identifiers,
+            &script.address_identifiers,
+        )
+    }
+
+    fn extract_handles(
+        &mut self,
+        module_handles: &[ModuleHandle],
+        identifiers: &[Identifier],
+        address_identifiers: &[AccountAddress],
+    ) -> Result<()> {
         for module_handle in module_handles {
-            let name = module.identifiers[module_handle.name.0 as usize]
+            let name = identifiers[module_handle.name.0 as usize]
                 .as_str()
                 .to_owned();
-            let address = module.address_identifiers[module_handle.address.0 as usize];
+            let address = address_identifiers[module_handle.address.0 as usize];
             self.imports
                 .insert(ModuleId::new(address, Identifier::new(name)?));
         }
@@ -7,20 +7,23 @@ pub mod store;
 
 use std::path::{PathBuf, Path};
 use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
 use anyhow::Error;
 use crate::manifest::{Dependence, MANIFEST, read_manifest};
 use crate::context::Context;
 use std::fs;
-use std::str::FromStr;
 use crate::index::store::{Module, SourceType};
 use resolver::git::GitIndex;
 use std::rc::Rc;
 use walkdir::{WalkDir, DirEntry};
-use crate::index::meta::{source_meta, FileMeta};
-use resolver::{git};
+use crate::index::meta::{source_meta, FileMeta, module_id, parse_module_id};
+use resolver::{binary, git};
+use resolver::binary::BinaryIndex;
 use crate::index::resolver::chain;
 use crate::index::resolver::chain::ChainIndex;
+use crate::lock::DoveLock;
 use diem::prelude::*;
+use lang::compiler::address::ProvidedAccountAddress;
 
 /// Modules index.
 pub type ModulesIndex = HashMap<Rc<ModuleId>, HashMap<SourceType, Module>>;
@@ -31,6 +34,8 @@ pub struct Index<'a> {
     pub modules: ModulesIndex,
     /// Set of dependencies names.
     pub dep_names: HashSet<Rc<str>>,
+    /// Absolute paths of the local dependencies including the transitive ones.
+    pub local_deps: Vec<PathBuf>,
     /// Dove context.
     pub ctx: &'a Context,
 }
@@ -43,52 +48,69 @@ impl<'a> Index<'a> {
             fs::create_dir_all(&deps_path)?;
         }
 
+        let mut local_deps = vec![];
+        let mut external_deps = HashSet::new();
         if let Some(dependencies) = &self.ctx.manifest.package.dependencies {
-            self.load_deps(&dependencies.deps)?;
+            let locked = DoveLock::load(self.ctx)?;
+            let mut lock = DoveLock::default();
+            self.load_deps(
+                &dependencies.deps,
+                &self.ctx.project_dir,
+                &locked,
+                &mut lock,
+                &mut local_deps,
+                &mut external_deps,
+            )?;
+            if lock != locked {
+                lock.store(self.ctx)?;
+            }
         }
+        self.remove_stale_deps(&deps_path, &external_deps)?;
 
         self.modules.iter_mut().for_each(|(_, m)| {
             m.remove(&SourceType::Local);
         });
 
-        let deps_dir = deps_path.read_dir()?;
+        // Dependencies are indexed in the name order to make `allow_duplicates` deterministic.
+        let mut deps_dir = deps_path.read_dir()?.collect::<Result<Vec<_>, _>>()?;
+        deps_dir.sort_by_key(|dir| dir.file_name());
         let mut new_deps = HashSet::new();
         for dir in deps_dir {
-            let dir = dir?;
             let name = Rc::from(dir.file_name().to_str().ok_or_else(|| {
                 anyhow!("Failed to convert dependence name:{:?}", dir.file_name())
             })?);
 
             let path = dir.path();
+            if is_external(&name) && !external_deps.contains(name.as_ref()) {
+                continue;
+            }
+            if name.starts_with(chain::PREFIX) && !chain::is_current(self.ctx, &name) {
+                continue;
+            }
             if !self.dep_names.contains(&name) {
                 if name.starts_with(git::PREFIX) {
                     let git = GitIndex::new(self.ctx, &path);
-                    self.store_meta(git.meta()?, SourceType::Git, name.clone());
+                    self.store_meta(git.meta()?, SourceType::Git, name.clone())?;
+                } else if name.starts_with(binary::PREFIX) {
+                    let binary = BinaryIndex::new(self.ctx, &path);
+                    self.store_meta(binary.meta()?, SourceType::Binary, name.clone())?;
                 } else if name.starts_with(chain::PREFIX) {
                     let chain = ChainIndex::new(self.ctx, &path);
-                    self.store_meta(chain.meta()?, SourceType::Chain, name.clone());
-                    chain.meta()?;
+                    self.store_meta(chain.meta()?, SourceType::Chain, name.clone())?;
                 }
-                new_deps.insert(name.clone());
             }
+            new_deps.insert(name);
         }
 
         self.dep_names = new_deps;
 
-        if let Some(dependencies) = &self.ctx.manifest.package.dependencies {
-            dependencies
-                .deps
-                .iter()
-                .filter_map(|dep| {
-                    if let Dependence::Path(path) = dep {
-                        Some(path.path.as_str())
-                    } else {
-                        None
-                    }
-                })
-                .map(|path| self.index_deps_for(&path))
-                .collect::<Result<Vec<_>, Error>>()?;
+        // Local dependencies of git dependencies are indexed with their repository.
+        let deps_path = deps_path.canonicalize()?;
+        local_deps.retain(|path| !path.starts_with(&deps_path));
+        for path in &local_deps {
+            self.index_deps_for(path)?;
         }
+        self.local_deps = local_deps;
 
         self.store()?;
         Ok(())
@@ -101,6 +123,7 @@ impl<'a> Index<'a> {
     ) -> Result<HashSet<Rc<str>>, Error> {
         let mut modules = HashSet::new();
         let mut imports = HashSet::new();
+        let addresses = self.ctx.named_addresses()?;
 
         for path in paths {
             let path = path.as_ref();
@@ -109,6 +132,7 @@ impl<'a> Index<'a> {
                     path,
                     Some(self.ctx.account_address()?.as_account_address()),
                     self.ctx.dialect.as_ref(),
+                    &addresses,
                 )?;
                 for meta in f_meta.meta {
                     modules.insert(meta.module_id);
@@ -120,6 +144,7 @@ impl<'a> Index<'a> {
                         mv_file.path(),
                         Some(self.ctx.account_address()?.as_account_address()),
                         self.ctx.dialect.as_ref(),
+                        &addresses,
                     )?;
 
                     for meta in f_meta.meta {
@@ -138,7 +163,7 @@ impl<'a> Index<'a> {
         let modules_count = self.modules.len();
 
         let mut deps = HashSet::new();
-        self.resolve_imports(&imports, &mut deps)?;
+        self.resolve_imports(&imports, &mut deps, &mut HashSet::new(), &mut vec![])?;
 
         if modules_count != self.modules.len() {
             self.store()?;
@@ -146,20 +171,40 @@ impl<'a> Index<'a> {
         Ok(deps)
     }
 
+    /// Resolves the imports and their transitive dependencies.
+    /// `visited` holds already resolved modules, `stack` holds the current import chain.
     fn resolve_imports(
         &mut self,
         imports: &HashSet<Rc<ModuleId>>,
         deps: &mut HashSet<Rc<str>>,
+        visited: &mut HashSet<Rc<ModuleId>>,
+        stack: &mut Vec<Rc<ModuleId>>,
     ) -> Result<(), Error> {
         fn resolve<'b>(
             index: &mut Index<'b>,
             import: &Rc<ModuleId>,
             deps: &mut HashSet<Rc<str>>,
+            visited: &mut HashSet<Rc<ModuleId>>,
+            stack: &mut Vec<Rc<ModuleId>>,
         ) -> Result<bool, Error> {
+            if let Some(pos) = stack.iter().position(|id| id == import) {
+                let cycle = stack[pos..]
+                    .iter()
+                    .chain(std::iter::once(import))
+                    .map(|id| module_id(id))
+                    .collect::<Vec<_>>();
+                return Err(anyhow!("Cyclic module dependency: {}", cycle.join(" -> ")));
+            }
+
             if let Some(module) = index.get_module(&import) {
+                if !visited.insert(import.clone()) {
+                    return Ok(true);
+                }
                 deps.insert(module.path.clone());
                 let imports = module.dependencies.clone();
-                index.resolve_imports(&imports, deps)?;
+                stack.push(import.clone());
+                index.resolve_imports(&imports, deps, visited, stack)?;
+                stack.pop();
                 Ok(true)
             } else {
                 Ok(false)
@@ -167,7 +212,7 @@ impl<'a> Index<'a> {
         }
 
         for import in imports {
-            if !resolve(self, import, deps)? {
+            if !resolve(self, import, deps, visited, stack)? {
                 let path = chain::resolve(self.ctx, import)?;
                 let index = ChainIndex::new(self.ctx, &path);
                 let name = path
@@ -178,15 +223,17 @@ impl<'a> Index<'a> {
 
                 let files_meta = index.meta()?;
 
+                stack.push(import.clone());
                 for file in &files_meta {
                     for module in &file.meta {
-                        self.resolve_imports(&module.imports, deps)?;
+                        self.resolve_imports(&module.imports, deps, visited, stack)?;
                     }
                 }
+                stack.pop();
 
-                self.store_meta(files_meta, SourceType::Chain, name);
+                self.store_meta(files_meta, SourceType::Chain, name)?;
 
-                if !resolve(self, import, deps)? {
+                if !resolve(self, import, deps, visited, stack)? {
                     return Err(anyhow!("Failed to resolve dependency:{:?}", import));
                 }
             }
@@ -195,13 +242,47 @@ impl<'a> Index<'a> {
         Ok(())
     }
 
-    fn get_module(&self, name: &Rc<ModuleId>) -> Option<&Module> {
+    /// Returns the addresses of the dependency files that have their own manifest.
+    /// Address-less modules of these files are compiled at the dependency address
+    /// instead of the project one.
+    pub fn dep_senders(
+        &self,
+        deps: &HashSet<Rc<str>>,
+    ) -> Result<HashMap<String, ProvidedAccountAddress>, Error> {
+        let target_deps = self.ctx.path_for(&self.ctx.manifest.layout.target_deps);
+        let mut settings = HashMap::new();
+        let mut senders = HashMap::new();
+        for module in self.modules.values().flat_map(|modules| modules.values()) {
+            if !deps.contains(&module.path) {
+                continue;
+            }
+            let dep_dir = match module.source_type {
+                SourceType::Local => PathBuf::from(module.dep_name.as_ref()),
+                SourceType::Git => target_deps.join(module.dep_name.as_ref()),
+                SourceType::Binary | SourceType::Chain => continue,
+            };
+            let dep_settings = match settings.entry(module.dep_name.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(resolver::dep_settings(self.ctx, &dep_dir)?),
+            };
+            if let Some(address) = dep_settings {
+                let sender = self
+                    .ctx
+                    .dialect
+                    .normalize_account_address(&format!("0x{}", address))?;
+                senders.insert(module.path.to_string(), sender);
+            }
+        }
+        Ok(senders)
+    }
+
+    /// Returns the module by its identifier.
+    /// Local modules take precedence over git, binary and chain ones.
+    pub fn get_module(&self, name: &Rc<ModuleId>) -> Option<&Module> {
         self.modules.get(name).and_then(|modules| {
-            modules.get(&SourceType::Local).or_else(|| {
-                modules
-                    .get(&SourceType::Git)
-                    .or_else(|| modules.get(&SourceType::Chain))
-            })
+            modules
+                .values()
+                .min_by_key(|module| precedence(module.source_type))
         })
     }
 
@@ -213,43 +294,98 @@ impl<'a> Index<'a> {
                 .to_owned(),
         );
 
+        let dep_address = match resolver::dep_settings(self.ctx, path.as_ref())? {
+            Some(address) => address,
+            None => self.ctx.account_address()?.as_account_address(),
+        };
+        let addresses = self.ctx.named_addresses()?;
+
         for file in move_dir_iter(path) {
             let meta = source_meta(
                 file.path(),
-                Some(self.ctx.account_address()?.as_account_address()),
+                Some(dep_address),
                 self.ctx.dialect.as_ref(),
+                &addresses,
             )?;
 
-            self.store_meta(vec![meta], SourceType::Local, dep_name.clone());
+            self.store_meta(vec![meta], SourceType::Local, dep_name.clone())?;
         }
         Ok(())
     }
 
-    fn load_deps(&self, deps: &[Dependence]) -> Result<(), Error> {
+    /// Resolves dependencies and their transitive dependencies.
+    /// Relative paths of local dependencies are resolved against `base_dir`.
+    /// Absolute paths of all local dependencies are collected into `local_deps`,
+    /// names of the git and binary dependencies directories into `external_deps`.
+    fn load_deps(
+        &mut self,
+        deps: &[Dependence],
+        base_dir: &Path,
+        locked: &DoveLock,
+        lock: &mut DoveLock,
+        local_deps: &mut Vec<PathBuf>,
+        external_deps: &mut HashSet<String>,
+    ) -> Result<(), Error> {
         for dep in deps {
             match dep {
                 Dependence::Git(git) => {
-                    let path = git::resolve(&self.ctx, &git)?;
-                    let manifest = path.join(MANIFEST);
+                    let resolved = git::resolve(&self.ctx, &git, locked)?;
+                    if resolved.updated {
+                        self.forget_dep(&resolved.locked.name);
+                    }
+                    external_deps.insert(resolved.locked.name.clone());
+                    lock.insert(resolved.locked);
+
+                    let manifest = resolved.path.join(MANIFEST);
                     if manifest.exists() {
                         if let Ok(manifest) = read_manifest(&manifest) {
                             if let Some(dependencies) = manifest.package.dependencies {
-                                self.load_deps(&dependencies.deps)?;
+                                self.load_deps(
+                                    &dependencies.deps,
+                                    &resolved.path,
+                                    locked,
+                                    lock,
+                                    local_deps,
+                                    external_deps,
+                                )?;
                             }
                         }
                     }
                 }
+                Dependence::Binary(binary) => {
+                    let resolved = binary::resolve(&self.ctx, base_dir, binary)?;
+                    if resolved.updated {
+                        self.forget_dep(&resolved.name);
+                    }
+                    external_deps.insert(resolved.name);
+                }
                 Dependence::Path(path) => {
-                    let path = PathBuf::from_str(&path.path)?;
-                    let path = if path.is_absolute() {
-                        path
-                    } else {
-                        self.ctx.path_for(path)
-                    };
-
+                    let path = base_dir.join(&path.path);
                     if !path.exists() {
                         return Err(anyhow!("Unresolved dependencies path:{:?}", path));
                     }
+
+                    let path = path.canonicalize()?;
+                    if local_deps.contains(&path) {
+                        continue;
+                    }
+                    local_deps.push(path.clone());
+
+                    let manifest = path.join(MANIFEST);
+                    if manifest.exists() {
+                        if let Ok(manifest) = read_manifest(&manifest) {
+                            if let Some(dependencies) = manifest.package.dependencies {
+                                self.load_deps(
+                                    &dependencies.deps,
+                                    &path,
+                                    locked,
+                                    lock,
+                                    local_deps,
+                                    external_deps,
+                                )?;
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -257,13 +393,123 @@ impl<'a> Index<'a> {
         Ok(())
     }
 
-    fn store_meta(&mut self, f_meta: Vec<FileMeta>, src_type: SourceType, dep_name: Rc<str>) {
+    /// Forgets git and binary dependencies that are not reachable from the manifest or lock,
+    /// e.g. checkouts of the previous revision of a git dependency.
+    /// Stale binary stubs and chain modules cached from another loader or node are removed
+    /// as well. The external dependencies of a workspace are shared by its members,
+    /// so there they are only forgotten and are left for `dove clean`.
+    fn remove_stale_deps(
+        &mut self,
+        deps_path: &Path,
+        external_deps: &HashSet<String>,
+    ) -> Result<(), Error> {
+        let stale = self
+            .modules
+            .values()
+            .flat_map(|modules| modules.values())
+            .map(|module| module.dep_name.clone())
+            .chain(self.dep_names.iter().cloned())
+            .filter(|name| {
+                (is_external(name) && !external_deps.contains(name.as_ref()))
+                    || (name.starts_with(chain::PREFIX) && !chain::is_current(self.ctx, name))
+            })
+            .collect::<HashSet<_>>();
+        for name in stale {
+            self.forget_dep(&name);
+        }
+
+        for dir in deps_path.read_dir()? {
+            let dir = dir?;
+            let name = dir.file_name().to_string_lossy().into_owned();
+            if name.starts_with(binary::PREFIX) && !external_deps.contains(&name) {
+                self.forget_dep(&name);
+                if self.ctx.workspace_dir.is_none() {
+                    fs::remove_dir_all(dir.path())?;
+                }
+            }
+            // Modules cached from another chain loader or node.
+            if name.starts_with(chain::PREFIX) && !chain::is_current(self.ctx, &name) {
+                self.forget_dep(&name);
+                if self.ctx.workspace_dir.is_none() {
+                    fs::remove_file(dir.path())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes all modules of the dependency from the index.
+    fn forget_dep(&mut self, dep_name: &str) {
+        self.dep_names.remove(dep_name);
+        for modules in self.modules.values_mut() {
+            modules.retain(|_, module| module.dep_name.as_ref() != dep_name);
+        }
+        self.modules.retain(|_, modules| !modules.is_empty());
+    }
+
+    /// Stores modules of the dependency in the index.
+    /// Returns an error if a module is already defined by another local, git or binary dependency,
+    /// unless the module is listed in the `allow_duplicates` of the manifest.
+    /// In this case the definition of the preferred source type is used and, within one source
+    /// type, the definition with the least dependency name and path is kept.
+    fn store_meta(
+        &mut self,
+        f_meta: Vec<FileMeta>,
+        src_type: SourceType,
+        dep_name: Rc<str>,
+    ) -> Result<(), Error> {
+        let ctx = self.ctx;
         for file in f_meta {
             for unit in file.meta {
                 let name = Rc::new(unit.module_id);
                 let entry = self.modules.entry(name.clone());
                 let modules = entry.or_insert_with(HashMap::default);
 
+                let conflict = modules
+                    .values()
+                    .filter(|existing| {
+                        existing.source_type != SourceType::Chain
+                            && src_type != SourceType::Chain
+                            && (existing.dep_name != dep_name || existing.path != file.path)
+                    })
+                    .min_by_key(|existing| precedence(existing.source_type));
+                if let Some(existing) = conflict {
+                    let message = format!(
+                        "Module {} is defined in both '{}' [{}] and '{}' [{}]",
+                        module_id(&name),
+                        existing.dep_name,
+                        existing.path,
+                        dep_name,
+                        file.path
+                    );
+                    if !is_duplicate_allowed(ctx, &name)? {
+                        return Err(anyhow!(
+                            "{}. Remove one of the definitions or list the module in \
+                             'allow_duplicates' of the {}",
+                            message,
+                            MANIFEST
+                        ));
+                    }
+
+                    let keep_existing = match modules.get(&src_type) {
+                        Some(same_type) => {
+                            (&same_type.dep_name, &same_type.path) <= (&dep_name, &file.path)
+                        }
+                        None => false,
+                    };
+                    let used = if keep_existing
+                        || precedence(existing.source_type) < precedence(src_type)
+                    {
+                        &existing.path
+                    } else {
+                        &file.path
+                    };
+                    eprintln!("warning: {}. Using '{}'.", message, used);
+                    if keep_existing {
+                        continue;
+                    }
+                }
+
                 let dependencies = unit.imports.into_iter().collect();
 
                 modules.insert(
@@ -278,7 +524,33 @@ impl<'a> Index<'a> {
                 );
             }
         }
+        Ok(())
+    }
+}
+
+/// Returns `true` if the directory name belongs to a git or binary dependency.
+fn is_external(dep_name: &str) -> bool {
+    dep_name.starts_with(git::PREFIX) || dep_name.starts_with(binary::PREFIX)
+}
+
+/// Returns the rank of the source type in the module lookup, the lower the preferred.
+fn precedence(source_type: SourceType) -> u8 {
+    match source_type {
+        SourceType::Local => 0,
+        SourceType::Git => 1,
+        SourceType::Binary => 2,
+        SourceType::Chain => 3,
+    }
+}
+
+/// Returns `true` if the module is listed in the `allow_duplicates` of the manifest.
+fn is_duplicate_allowed(ctx: &Context, id: &ModuleId) -> Result<bool, Error> {
+    for allowed in &ctx.manifest.package.allow_duplicates {
+        if &parse_module_id(ctx, allowed)? == id {
+            return Ok(true);
+        }
     }
+    Ok(false)
 }
 
 /// Creates an iterator from move files
@@ -294,3 +566,169 @@ pub fn move_dir_iter<P: AsRef<Path>>(path: P) -> impl Iterator<Item = DirEntry>
                 .unwrap_or(false)
         })
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+    use std::rc::Rc;
+
+    use diem::prelude::*;
+    use lang::compiler::dialects::DialectName;
+
+    use crate::context::Context;
+    use crate::index::Index;
+    use crate::index::meta::{DefinitionMeta, FileMeta, module_id};
+    use crate::index::store::SourceType;
+    use crate::manifest::DoveToml;
+
+    fn context(allow_duplicates: &[&str]) -> Context {
+        let mut manifest = DoveToml::default();
+        manifest.package.allow_duplicates =
+            allow_duplicates.iter().map(|id| id.to_string()).collect();
+        Context {
+            project_dir: std::env::temp_dir(),
+            manifest,
+            dialect: DialectName::DFinance.get_dialect(),
+            workspace_dir: None,
+            profile: None,
+        }
+    }
+
+    fn id(name: &str) -> ModuleId {
+        ModuleId::new(CORE_CODE_ADDRESS, Identifier::new(name).unwrap())
+    }
+
+    fn meta(path: &str, name: &str, imports: &[&str]) -> Vec<FileMeta> {
+        vec![FileMeta {
+            path: Rc::from(path),
+            meta: vec![DefinitionMeta {
+                module_id: id(name),
+                script: None,
+                imports: imports.iter().map(|name| Rc::new(id(name))).collect(),
+            }],
+        }]
+    }
+
+    fn new_index(ctx: &Context) -> Index {
+        Index {
+            modules: Default::default(),
+            dep_names: Default::default(),
+            local_deps: Default::default(),
+            ctx,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_modules() {
+        let ctx = context(&[]);
+        let mut index = new_index(&ctx);
+        index
+            .store_meta(
+                meta("a/coins.move", "Coins", &[]),
+                SourceType::Git,
+                "a".into(),
+            )
+            .unwrap();
+        index
+            .store_meta(
+                meta("a/coins.move", "Coins", &[]),
+                SourceType::Git,
+                "a".into(),
+            )
+            .unwrap();
+        let err = index
+            .store_meta(
+                meta("b/coins.move", "Coins", &[]),
+                SourceType::Git,
+                "b".into(),
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'a' [a/coins.move]"));
+        assert!(err.contains("'b' [b/coins.move]"));
+
+        let err = index
+            .store_meta(
+                meta("l/coins.move", "Coins", &[]),
+                SourceType::Local,
+                "l".into(),
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'a' [a/coins.move]"));
+        assert!(err.contains("'l' [l/coins.move]"));
+
+        let ctx = context(&["0x1::Coins"]);
+        let mut index = new_index(&ctx);
+        index
+            .store_meta(
+                meta("b/coins.move", "Coins", &[]),
+                SourceType::Git,
+                "b".into(),
+            )
+            .unwrap();
+        index
+            .store_meta(
+                meta("a/coins.move", "Coins", &[]),
+                SourceType::Git,
+                "a".into(),
+            )
+            .unwrap();
+        index
+            .store_meta(
+                meta("c/coins.move", "Coins", &[]),
+                SourceType::Git,
+                "c".into(),
+            )
+            .unwrap();
+        let module = index.get_module(&Rc::new(id("Coins"))).unwrap();
+        assert_eq!(module.dep_name.as_ref(), "a");
+
+        index
+            .store_meta(
+                meta("d/coins.move", "Coins", &[]),
+                SourceType::Binary,
+                "d".into(),
+            )
+            .unwrap();
+        index
+            .store_meta(
+                meta("l/coins.move", "Coins", &[]),
+                SourceType::Local,
+                "l".into(),
+            )
+            .unwrap();
+        let module = index.get_module(&Rc::new(id("Coins"))).unwrap();
+        assert_eq!(module.dep_name.as_ref(), "l");
+    }
+
+    #[test]
+    fn test_cyclic_imports() {
+        let ctx = context(&[]);
+        let mut index = new_index(&ctx);
+        index
+            .store_meta(meta("a.move", "A", &["B"]), SourceType::Git, "a".into())
+            .unwrap();
+        index
+            .store_meta(meta("b.move", "B", &["A"]), SourceType::Git, "a".into())
+            .unwrap();
+
+        let imports = vec![Rc::new(id("A"))].into_iter().collect();
+        let err = index
+            .resolve_imports(
+                &imports,
+                &mut HashSet::new(),
+                &mut HashSet::new(),
+                &mut vec![],
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Cyclic module dependency:"));
+        let cycle = format!(
+            "{a} -> {b} -> {a}",
+            a = module_id(&id("A")),
+            b = module_id(&id("B"))
+        );
+        assert!(err.ends_with(&cycle));
+    }
+}
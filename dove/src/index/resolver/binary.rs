@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Error;
+use diem::prelude::CompiledModule;
+use diem::vm::access::ModuleAccess;
+use lang::compiler::address::NamedAddresses;
+use tiny_keccak::{Hasher, Sha3};
+use walkdir::WalkDir;
+
+use crate::cmd::build::ModulePackage;
+use crate::context::Context;
+use crate::index::meta::{source_meta, FileMeta};
+use crate::index::move_dir_iter;
+use crate::index::resolver::interface_stub;
+use crate::manifest::DepBinary;
+
+/// Cache prefix.
+pub const PREFIX: &str = "binary";
+
+/// File with the hash of the bytecode the stubs were generated from.
+const HASH_FILE: &str = ".hash";
+
+/// Resolved binary dependency.
+pub struct Resolved {
+    /// Local name of the dependency in the external dependencies directory.
+    pub name: String,
+    /// `true` if the stubs were regenerated.
+    pub updated: bool,
+}
+
+/// Generates interface stubs of the compiled modules into the external dependencies directory.
+/// Stubs are regenerated only if the bytecode has changed.
+pub fn resolve(ctx: &Context, base_dir: &Path, dep: &DepBinary) -> Result<Resolved, Error> {
+    let path = base_dir.join(&dep.path);
+    if !path.exists() {
+        return Err(anyhow!("Unresolved binary dependency path:{:?}", path));
+    }
+    let path = path.canonicalize()?;
+
+    let modules = load_bytecode(&path)?;
+    let hash = bytecode_hash(&modules);
+    let name = make_local_name(&path);
+    let dir = ctx.path_for(&ctx.manifest.layout.target_deps).join(&name);
+
+    let is_actual = fs::read_to_string(dir.join(HASH_FILE))
+        .map(|stored| stored == hash)
+        .unwrap_or(false);
+    if is_actual {
+        return Ok(Resolved {
+            name,
+            updated: false,
+        });
+    }
+
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+
+    for bytecode in modules {
+        let id = CompiledModule::deserialize(&bytecode)
+            .map_err(|err| anyhow!("Failed to deserialize module from {:?}: {:?}", path, err))?
+            .self_id();
+        let stub = interface_stub(&bytecode)?;
+        fs::write(
+            dir.join(format!("{}_{}.move", id.address(), id.name())),
+            stub,
+        )?;
+    }
+    fs::write(dir.join(HASH_FILE), hash)?;
+
+    Ok(Resolved {
+        name,
+        updated: true,
+    })
+}
+
+/// Index of binary dependencies.
+pub struct BinaryIndex<'a> {
+    ctx: &'a Context,
+    path: &'a Path,
+}
+
+impl<'a> BinaryIndex<'a> {
+    /// Create a new `BinaryIndex` instance.
+    pub fn new(ctx: &'a Context, path: &'a Path) -> BinaryIndex<'a> {
+        BinaryIndex { ctx, path }
+    }
+
+    /// Returns metadata of the generated stubs.
+    pub fn meta(&self) -> Result<Vec<FileMeta>, Error> {
+        move_dir_iter(self.path)
+            .map(|stub| {
+                source_meta(
+                    stub.path(),
+                    None,
+                    self.ctx.dialect.as_ref(),
+                    &NamedAddresses::default(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Loads module bytecode from the `.mv` file, `.pac` package or directory with them.
+fn load_bytecode(path: &Path) -> Result<Vec<Vec<u8>>, Error> {
+    if path.is_file() {
+        return load_file(path);
+    }
+
+    let mut files = WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.is_file() && (has_extension(path, "mv") || has_extension(path, "pac"))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let mut modules = vec![];
+    for file in files {
+        modules.extend(load_file(&file)?);
+    }
+    Ok(modules)
+}
+
+fn load_file(path: &Path) -> Result<Vec<Vec<u8>>, Error> {
+    let bytes = fs::read(path)?;
+    if has_extension(path, "pac") {
+        Ok(ModulePackage::decode(&bytes)
+            .map_err(|err| anyhow!("Failed to decode module package {:?}: {}", path, err))?
+            .modules)
+    } else if has_extension(path, "mv") {
+        Ok(vec![bytes])
+    } else {
+        Err(anyhow!(
+            "Unsupported binary dependency {:?}. Expected .mv file, .pac package or directory.",
+            path
+        ))
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case(extension))
+        .unwrap_or(false)
+}
+
+fn bytecode_hash(modules: &[Vec<u8>]) -> String {
+    let mut digest = Sha3::v256();
+    for module in modules {
+        digest.update(&(module.len() as u64).to_le_bytes());
+        digest.update(module);
+    }
+    let mut output = [0; 32];
+    digest.finalize(&mut output);
+    hex::encode(&output)
+}
+
+fn make_local_name(path: &Path) -> String {
+    let mut digest = Sha3::v256();
+    digest.update(path.to_string_lossy().as_bytes());
+    let mut output = [0; 32];
+    digest.finalize(&mut output);
+    format!("{}_{}", PREFIX, hex::encode(&output))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use diem::move_lang::name_pool::ConstPool;
+    use diem::prelude::*;
+    use lang::builder::{Artifacts, MoveBuilder};
+    use lang::compiler::dialects::DialectName;
+    use lang::compiler::file::MoveFile;
+
+    use crate::cmd::build::ModulePackage;
+    use crate::context::Context;
+    use crate::index::resolver::binary::{load_bytecode, resolve, BinaryIndex};
+    use crate::index::store::SourceType;
+    use crate::manifest::{DepBinary, Dependence, Dependencies, DoveToml};
+
+    const COINS: &str = "address 0x1 { module Coins { public fun value(): u64 { 1 } } }";
+    const EVENT: &str = "address 0x1 { module Event { public fun emit() {} } }";
+
+    fn compile(source: &str) -> Vec<u8> {
+        let dialect = DialectName::DFinance.get_dialect();
+        let sender = dialect.normalize_account_address("0x1").unwrap();
+        let Artifacts { prog, .. } = MoveBuilder::new(dialect.as_ref(), Some(&sender))
+            .build(&[MoveFile::with_content("module.move", source)], &[]);
+        prog.unwrap().remove(0).serialize()
+    }
+
+    fn context(project_dir: &Path, binary: Option<&Path>) -> Context {
+        let mut manifest = DoveToml::default();
+        manifest.package.dependencies = binary.map(|path| Dependencies {
+            deps: vec![Dependence::Binary(DepBinary {
+                path: path.to_string_lossy().into_owned(),
+            })],
+        });
+        Context {
+            project_dir: project_dir.to_owned(),
+            manifest,
+            dialect: DialectName::DFinance.get_dialect(),
+            workspace_dir: None,
+            profile: None,
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}", name, rand::random::<u32>()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn stubs(dir: &Path) -> Vec<String> {
+        let mut stubs = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".move"))
+            .collect::<Vec<_>>();
+        stubs.sort();
+        stubs
+    }
+
+    #[test]
+    fn test_load_bytecode() {
+        let _pool = ConstPool::new();
+        let coins = compile(COINS);
+        let event = compile(EVENT);
+
+        let dir = temp_dir("dove_binary");
+        fs::write(dir.join("Coins.mv"), &coins).unwrap();
+        let package = ModulePackage {
+            modules: vec![event.clone(), coins.clone()],
+        };
+        fs::create_dir_all(dir.join("pac")).unwrap();
+        fs::write(dir.join("pac").join("std.pac"), package.encode().unwrap()).unwrap();
+        fs::write(dir.join("readme.txt"), "not a module").unwrap();
+
+        assert_eq!(
+            load_bytecode(&dir.join("Coins.mv")).unwrap(),
+            vec![coins.clone()]
+        );
+        assert_eq!(
+            load_bytecode(&dir.join("pac").join("std.pac")).unwrap(),
+            vec![event.clone(), coins.clone()]
+        );
+        // Directories are loaded in the file name order, other files are skipped.
+        assert_eq!(
+            load_bytecode(&dir).unwrap(),
+            vec![coins.clone(), event, coins]
+        );
+        assert!(load_bytecode(&dir.join("readme.txt")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_stubs() {
+        let _pool = ConstPool::new();
+        let project_dir = temp_dir("dove_binary_project");
+        let modules = project_dir.join("modules");
+        fs::create_dir_all(&modules).unwrap();
+        fs::write(modules.join("Coins.mv"), compile(COINS)).unwrap();
+        fs::write(modules.join("Event.mv"), compile(EVENT)).unwrap();
+
+        let ctx = context(&project_dir, None);
+        let dep = DepBinary {
+            path: "modules".to_owned(),
+        };
+        let resolved = resolve(&ctx, &project_dir, &dep).unwrap();
+        assert!(resolved.updated);
+        let dir = ctx
+            .path_for(&ctx.manifest.layout.target_deps)
+            .join(&resolved.name);
+        assert_eq!(stubs(&dir).len(), 2);
+        let names = BinaryIndex::new(&ctx, &dir)
+            .meta()
+            .unwrap()
+            .into_iter()
+            .flat_map(|file| file.meta)
+            .map(|meta| meta.module_id.name().to_string())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"Coins".to_owned()));
+        assert!(names.contains(&"Event".to_owned()));
+
+        // Unchanged bytecode keeps the stubs.
+        let resolved = resolve(&ctx, &project_dir, &dep).unwrap();
+        assert!(!resolved.updated);
+
+        // Stubs of the removed modules are removed with the regenerated stubs.
+        let coins_stub = stubs(&dir)
+            .into_iter()
+            .find(|name| name.contains("Coins"))
+            .unwrap();
+        fs::remove_file(modules.join("Event.mv")).unwrap();
+        let resolved = resolve(&ctx, &project_dir, &dep).unwrap();
+        assert!(resolved.updated);
+        assert_eq!(stubs(&dir), vec![coins_stub]);
+
+        assert!(resolve(
+            &ctx,
+            &project_dir,
+            &DepBinary {
+                path: "missing".to_owned()
+            }
+        )
+        .is_err());
+
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_stale_binary() {
+        let _pool = ConstPool::new();
+        let project_dir = temp_dir("dove_binary_index");
+        let binary = project_dir.join("Coins.mv");
+        fs::write(&binary, compile(COINS)).unwrap();
+        let coins = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Coins").unwrap());
+
+        let ctx = context(&project_dir, Some(&binary));
+        let index = ctx.build_index().unwrap();
+        let module = index.get_module(&coins.clone().into()).unwrap();
+        assert_eq!(module.source_type, SourceType::Binary);
+        let stub_dir = ctx
+            .path_for(&ctx.manifest.layout.target_deps)
+            .join(module.dep_name.as_ref());
+        assert!(stub_dir.exists());
+
+        // The dependency is removed from the manifest.
+        let ctx = context(&project_dir, None);
+        let index = ctx.build_index().unwrap();
+        assert!(index.get_module(&coins.into()).is_none());
+        assert!(!stub_dir.exists());
+
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+}
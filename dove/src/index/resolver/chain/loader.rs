@@ -1,13 +1,19 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use anyhow::Result;
 
 use diem::prelude::*;
+use diem::vm::access::ModuleAccess;
 
 use tiny_keccak::{Hasher, Sha3};
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use http::Uri;
 use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+use crate::index::resolver::chain::substrate::SubstrateBytecodeLoader;
 
 /// Module loader.
 pub trait BytecodeLoader: Clone {
@@ -26,6 +32,27 @@ impl BytecodeLoader for ZeroLoader {
     }
 }
 
+/// Bytecode loader selected in the manifest.
+#[derive(Clone)]
+pub enum ChainLoader {
+    /// dnode REST api.
+    Rest(RestBytecodeLoader),
+    /// Local directory or chain-state snapshot.
+    Local(LocalBytecodeLoader),
+    /// Substrate node JSON-RPC api.
+    Substrate(SubstrateBytecodeLoader),
+}
+
+impl BytecodeLoader for ChainLoader {
+    fn load(&self, module_id: ModuleId) -> Result<Vec<u8>> {
+        match self {
+            ChainLoader::Rest(loader) => loader.load(module_id),
+            ChainLoader::Local(loader) => loader.load(module_id),
+            ChainLoader::Substrate(loader) => loader.load(module_id),
+        }
+    }
+}
+
 /// Bytecode loader which loads bytecode by dnode REST api.
 #[derive(Clone)]
 pub struct RestBytecodeLoader {
@@ -64,6 +91,77 @@ impl BytecodeLoader for RestBytecodeLoader {
     }
 }
 
+/// Bytecode loader which loads bytecode from a local directory with `.mv` files
+/// or from a chain-state snapshot.
+/// The snapshot is a JSON object with hex encoded module bytecode values.
+/// Modules are keyed by the identifiers from their bytecode.
+#[derive(Clone)]
+pub struct LocalBytecodeLoader {
+    modules: Rc<HashMap<ModuleId, Vec<u8>>>,
+}
+
+impl LocalBytecodeLoader {
+    /// Reads modules from the directory or the snapshot file.
+    pub fn open(path: &Path) -> Result<LocalBytecodeLoader> {
+        let bytecode = if path.is_dir() {
+            WalkDir::new(path)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .filter(|entry| {
+                    entry
+                        .path()
+                        .extension()
+                        .map(|ext| ext == "mv")
+                        .unwrap_or(false)
+                })
+                .map(|entry| Ok(fs::read(entry.path())?))
+                .collect::<Result<Vec<_>>>()?
+        } else if path.is_file() {
+            let snapshot: BTreeMap<String, String> =
+                serde_json::from_str(&fs::read_to_string(path)?).map_err(|err| {
+                    anyhow!("Failed to read chain-state snapshot {:?}: {}", path, err)
+                })?;
+            snapshot
+                .into_iter()
+                .map(|(key, value)| {
+                    hex::decode(value.trim_start_matches("0x")).map_err(|err| {
+                        anyhow!("Invalid bytecode of {} in {:?}: {}", key, path, err)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            return Err(anyhow!("Chain-state path {:?} not found", path));
+        };
+
+        let modules = bytecode
+            .into_iter()
+            .map(|bytecode| {
+                let id = CompiledModule::deserialize(&bytecode)
+                    .map_err(|err| anyhow!("Failed to deserialize module: {:?}", err))?
+                    .self_id();
+                Ok((id, bytecode))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(LocalBytecodeLoader {
+            modules: Rc::new(modules),
+        })
+    }
+}
+
+impl BytecodeLoader for LocalBytecodeLoader {
+    fn load(&self, module_id: ModuleId) -> Result<Vec<u8>> {
+        self.modules.get(&module_id).cloned().ok_or_else(|| {
+            anyhow!(
+                "Module {}::{} not found in the local chain state",
+                module_id.address(),
+                module_id.name()
+            )
+        })
+    }
+}
+
 /// Api response.
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct LoaderResponse {
@@ -137,3 +235,47 @@ where
         hex::encode(&output)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::str::FromStr;
+
+    use diem::move_lang::name_pool::ConstPool;
+    use diem::prelude::*;
+    use lang::builder::{Artifacts, MoveBuilder};
+    use lang::compiler::dialects::DialectName;
+    use lang::compiler::file::MoveFile;
+
+    use crate::index::resolver::chain::loader::{BytecodeLoader, LocalBytecodeLoader};
+    use crate::manifest::default_dialect;
+
+    #[test]
+    fn test_local_loader() {
+        let _pool = ConstPool::new();
+
+        // The module is compiled in the address format of the default dialect.
+        let dialect = DialectName::from_str(&default_dialect())
+            .unwrap()
+            .get_dialect();
+        let std = dialect.normalize_account_address("0x1").unwrap();
+        let source = "address 0x1 { module Debug { public fun print() {} } }";
+        let Artifacts { prog, .. } = MoveBuilder::new(dialect.as_ref(), Some(&std))
+            .build(&[MoveFile::with_content("debug.move", source)], &[]);
+        let bytecode = prog.unwrap().remove(0).serialize();
+
+        let dir = std::env::temp_dir().join(format!("dove_loader_{}", rand::random::<u32>()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Debug.mv"), &bytecode).unwrap();
+        let loader = LocalBytecodeLoader::open(&dir).unwrap();
+
+        let std = std.as_account_address();
+        let debug = ModuleId::new(std, Identifier::new("Debug").unwrap());
+        assert_eq!(loader.load(debug).unwrap(), bytecode);
+
+        let missing = ModuleId::new(std, Identifier::new("Missing").unwrap());
+        assert!(loader.load(missing).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
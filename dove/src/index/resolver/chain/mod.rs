@@ -3,14 +3,19 @@ use std::path::{Path, PathBuf};
 use anyhow::Error;
 use crate::index::meta::{source_meta, FileMeta, extract_bytecode_dependencies};
 use tiny_keccak::{Sha3, Hasher};
-use loader::{RestBytecodeLoader, BytecodeLoader};
+use loader::{BytecodeLoader, ChainLoader, LocalBytecodeLoader, RestBytecodeLoader};
+use crate::manifest::ChainSource;
 use diem::prelude::ModuleId;
-use lang::disassembler::{Config, Disassembler, unit::CompiledUnit as Unit};
+use crate::index::resolver::interface_stub;
 use std::fs::OpenOptions;
 use std::io::Write;
+use lang::compiler::address::NamedAddresses;
+use substrate::SubstrateBytecodeLoader;
 
 /// Dependencies loader.
 pub mod loader;
+/// Substrate node dependencies loader.
+pub mod substrate;
 
 /// Cache prefix.
 pub const PREFIX: &str = "chain";
@@ -21,8 +26,7 @@ pub fn resolve(ctx: &Context, module_id: &ModuleId) -> Result<PathBuf, Error> {
     let dep = make_path(ctx, module_id);
 
     if !dep.exists() {
-        if let Some(chain_url) = &ctx.manifest.package.blockchain_api {
-            let loader = RestBytecodeLoader::new(chain_url.parse()?);
+        if let Some(loader) = chain_loader(ctx)? {
             load_tree(ctx, &loader, module_id)?;
         } else {
             return Err(anyhow!(
@@ -35,9 +39,40 @@ pub fn resolve(ctx: &Context, module_id: &ModuleId) -> Result<PathBuf, Error> {
     Ok(dep)
 }
 
-fn load_tree(
+/// Returns bytecode loader selected in the manifest.
+/// If the loader is not specified and the `blockchain_api` is, the substrate node api is used
+/// for the `polkadot` dialect and dnode REST api otherwise.
+pub fn chain_loader(ctx: &Context) -> Result<Option<ChainLoader>, Error> {
+    let blockchain_api = ctx.manifest.package.blockchain_api.as_ref();
+    let api_url = |loader: &str| {
+        blockchain_api
+            .ok_or_else(|| anyhow!("{} loader requires the 'blockchain_api' url", loader))
+    };
+    match &ctx.manifest.chain {
+        Some(ChainSource::Local { path }) => Ok(Some(ChainLoader::Local(
+            LocalBytecodeLoader::open(&ctx.path_for(path))?,
+        ))),
+        Some(ChainSource::Dnode) => Ok(Some(ChainLoader::Rest(RestBytecodeLoader::new(
+            api_url("dnode")?.parse()?,
+        )))),
+        Some(ChainSource::Substrate) => Ok(Some(ChainLoader::Substrate(
+            SubstrateBytecodeLoader::new(api_url("substrate")?.parse()?),
+        ))),
+        None => match blockchain_api {
+            Some(url) if ctx.dialect.name() == "polkadot" => Ok(Some(ChainLoader::Substrate(
+                SubstrateBytecodeLoader::new(url.parse()?),
+            ))),
+            Some(url) => Ok(Some(ChainLoader::Rest(RestBytecodeLoader::new(
+                url.parse()?,
+            )))),
+            None => Ok(None),
+        },
+    }
+}
+
+fn load_tree<L: BytecodeLoader>(
     ctx: &Context,
-    loader: &RestBytecodeLoader,
+    loader: &L,
     module_id: &ModuleId,
 ) -> Result<(), Error> {
     let bytecode = loader.load(module_id.to_owned())?;
@@ -45,13 +80,7 @@ fn load_tree(
         load_tree(ctx, loader, &import)?;
     }
 
-    let config = Config {
-        light_version: true,
-    };
-    let unit = Unit::new(&bytecode)?;
-    let disasm = Disassembler::new(&unit, config);
-    let source_unit = disasm.make_source_unit();
-    let signature = source_unit.code_string()?;
+    let signature = interface_stub(&bytecode)?;
 
     let mut f = OpenOptions::new()
         .create(true)
@@ -80,20 +109,96 @@ impl<'a> ChainIndex<'a> {
             self.path,
             None,
             self.ctx.dialect.as_ref(),
+            &NamedAddresses::default(),
         )?])
     }
 }
 
+/// Returns `true` if the cached module was loaded from the chain selected in the manifest.
+pub fn is_current(ctx: &Context, dep_name: &str) -> bool {
+    dep_name.starts_with(&format!("{}_{}_", PREFIX, chain_key(ctx)))
+}
+
+/// Returns the hash of the loader kind and its url or path.
+/// Modules loaded from different nodes or networks are cached under different keys.
+fn chain_key(ctx: &Context) -> String {
+    let api = ctx
+        .manifest
+        .package
+        .blockchain_api
+        .as_deref()
+        .unwrap_or_default();
+    let chain = match &ctx.manifest.chain {
+        Some(ChainSource::Local { path }) => format!("local:{}", ctx.path_for(path).display()),
+        Some(ChainSource::Dnode) => format!("dnode:{}", api),
+        Some(ChainSource::Substrate) => format!("substrate:{}", api),
+        None if ctx.dialect.name() == "polkadot" => format!("substrate:{}", api),
+        None => format!("dnode:{}", api),
+    };
+
+    let mut digest = Sha3::v256();
+    digest.update(chain.as_bytes());
+    let mut output = [0; 32];
+    digest.finalize(&mut output);
+    hex::encode(&output[..8])
+}
+
 fn make_path(ctx: &Context, module_id: &ModuleId) -> PathBuf {
     let deps_dir = ctx.path_for(&ctx.manifest.layout.target_deps);
-    deps_dir.join(make_local_name(module_id))
+    deps_dir.join(make_local_name(ctx, module_id))
 }
 
-fn make_local_name(module_name: &ModuleId) -> String {
+fn make_local_name(ctx: &Context, module_name: &ModuleId) -> String {
     let mut digest = Sha3::v256();
     digest.update(module_name.name().as_bytes());
     digest.update(module_name.address().as_ref().as_ref());
     let mut output = [0; 32];
     digest.finalize(&mut output);
-    format!("{}_{}", PREFIX, hex::encode(&output))
+    format!("{}_{}_{}", PREFIX, chain_key(ctx), hex::encode(&output))
+}
+
+#[cfg(test)]
+mod test {
+    use diem::prelude::*;
+    use lang::compiler::dialects::DialectName;
+
+    use crate::context::Context;
+    use crate::index::resolver::chain::{is_current, make_local_name};
+    use crate::manifest::{ChainSource, DoveToml};
+
+    fn context(chain: Option<ChainSource>, api: &str) -> Context {
+        let mut manifest = DoveToml::default();
+        manifest.chain = chain;
+        manifest.package.blockchain_api = Some(api.to_owned());
+        Context {
+            project_dir: std::env::temp_dir(),
+            manifest,
+            dialect: DialectName::DFinance.get_dialect(),
+            workspace_dir: None,
+            profile: None,
+        }
+    }
+
+    #[test]
+    fn test_chain_cache_key() {
+        let id = ModuleId::new(CORE_CODE_ADDRESS, Identifier::new("Coins").unwrap());
+        let testnet = context(None, "http://testnet:1317");
+        let name = make_local_name(&testnet, &id);
+        assert!(name.starts_with("chain_"));
+        assert!(is_current(&testnet, &name));
+        assert!(is_current(
+            &context(Some(ChainSource::Dnode), "http://testnet:1317"),
+            &name
+        ));
+
+        assert!(!is_current(&context(None, "http://mainnet:1317"), &name));
+        assert!(!is_current(
+            &context(Some(ChainSource::Substrate), "http://testnet:1317"),
+            &name
+        ));
+        assert_ne!(
+            make_local_name(&context(None, "http://mainnet:1317"), &id),
+            name
+        );
+    }
 }
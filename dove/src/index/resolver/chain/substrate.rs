@@ -0,0 +1,628 @@
+use std::convert::TryInto;
+use std::hash::Hasher;
+use std::rc::Rc;
+
+use anyhow::Result;
+use blake2_rfc::blake2b::blake2b;
+use diem::prelude::*;
+use http::Uri;
+use once_cell::unsync::OnceCell;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use twox_hash::XxHash64;
+
+use crate::index::resolver::chain::loader::BytecodeLoader;
+
+/// Pallet of the move virtual machine.
+pub const MODULE: &str = "Mvm";
+/// Storage of the move virtual machine.
+pub const STORAGE: &str = "VMStorage";
+
+/// Bytecode loader which loads bytecode from the `Mvm::VMStorage` storage of the substrate node
+/// by its JSON-RPC api.
+/// All modules are read at the finalized head of the first request.
+#[derive(Clone)]
+pub struct SubstrateBytecodeLoader {
+    url: Uri,
+    state: Rc<OnceCell<ChainState>>,
+}
+
+/// Finalized block and the storage hasher of the `Mvm::VMStorage` map at this block.
+struct ChainState {
+    block: String,
+    hasher: StorageHasher,
+}
+
+impl SubstrateBytecodeLoader {
+    /// Create a new `SubstrateBytecodeLoader` with the node JSON-RPC url.
+    pub fn new(url: Uri) -> SubstrateBytecodeLoader {
+        SubstrateBytecodeLoader {
+            url,
+            state: Default::default(),
+        }
+    }
+
+    fn state(&self) -> Result<&ChainState> {
+        self.state.get_or_try_init(|| {
+            let block: String = self
+                .call("chain_getFinalizedHead", vec![])?
+                .ok_or_else(|| anyhow!("Failed to get finalized head of '{}'", self.url))?;
+            let metadata: String = self
+                .call("state_getMetadata", vec![block.clone()])?
+                .ok_or_else(|| anyhow!("Failed to get runtime metadata of '{}'", self.url))?;
+            let hasher = vm_storage_hasher(&hex::decode(metadata.trim_start_matches("0x"))?)?;
+            Ok(ChainState { block, hasher })
+        })
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &'static str,
+        params: Vec<String>,
+    ) -> Result<Option<T>> {
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            id: 1,
+            method,
+            params,
+        };
+
+        let resp: RpcResponse<T> = reqwest::blocking::Client::new()
+            .post(&self.url.to_string())
+            .json(&request)
+            .send()?
+            .json()?;
+
+        match resp.error {
+            Some(error) => Err(anyhow!(
+                "Request {} to '{}' failed: {} [{}]",
+                method,
+                self.url,
+                error.message,
+                error.code
+            )),
+            None => Ok(resp.result),
+        }
+    }
+}
+
+impl BytecodeLoader for SubstrateBytecodeLoader {
+    fn load(&self, module_id: ModuleId) -> Result<Vec<u8>> {
+        let state = self.state()?;
+        let key = format!(
+            "0x{}",
+            hex::encode(vm_storage_key(&module_id.access_vector(), state.hasher))
+        );
+
+        let value: String = self
+            .call("state_getStorage", vec![key, state.block.clone()])
+            .map_err(|err| {
+                anyhow!(
+                    "Failed to load module {}::{}: {}",
+                    module_id.address(),
+                    module_id.name(),
+                    err
+                )
+            })?
+            .ok_or_else(|| {
+                anyhow!(
+                    "Module {}::{} not found on '{}'",
+                    module_id.address(),
+                    module_id.name(),
+                    self.url
+                )
+            })?;
+        decode_bytes(&hex::decode(value.trim_start_matches("0x"))?)
+    }
+}
+
+/// JSON-RPC request.
+#[derive(Serialize, Debug)]
+struct RpcRequest {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'static str,
+    params: Vec<String>,
+}
+
+/// JSON-RPC response.
+#[derive(Deserialize, Debug)]
+struct RpcResponse<T> {
+    #[serde(default = "Option::default")]
+    result: Option<T>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+/// JSON-RPC error.
+#[derive(Deserialize, Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Hasher of the storage map keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageHasher {
+    /// blake2 128.
+    Blake2_128,
+    /// blake2 256.
+    Blake2_256,
+    /// blake2 128 followed by the key.
+    Blake2_128Concat,
+    /// xxHash 128.
+    Twox128,
+    /// xxHash 256.
+    Twox256,
+    /// xxHash 64 followed by the key.
+    Twox64Concat,
+    /// The key itself.
+    Identity,
+}
+
+impl StorageHasher {
+    fn from_index(index: u8) -> Result<StorageHasher> {
+        Ok(match index {
+            0 => StorageHasher::Blake2_128,
+            1 => StorageHasher::Blake2_256,
+            2 => StorageHasher::Blake2_128Concat,
+            3 => StorageHasher::Twox128,
+            4 => StorageHasher::Twox256,
+            5 => StorageHasher::Twox64Concat,
+            6 => StorageHasher::Identity,
+            _ => return Err(anyhow!("Unknown storage hasher {}", index)),
+        })
+    }
+
+    fn hash(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            StorageHasher::Blake2_128 => blake2b(16, &[], data).as_bytes().to_vec(),
+            StorageHasher::Blake2_256 => blake2b(32, &[], data).as_bytes().to_vec(),
+            StorageHasher::Blake2_128Concat => [blake2b(16, &[], data).as_bytes(), data].concat(),
+            StorageHasher::Twox128 => twox(data, 2),
+            StorageHasher::Twox256 => twox(data, 4),
+            StorageHasher::Twox64Concat => [&twox(data, 1)[..], data].concat(),
+            StorageHasher::Identity => data.to_vec(),
+        }
+    }
+}
+
+/// Returns the storage key of the `Mvm::VMStorage` entry.
+/// The map key is the SCALE encoded path hashed with the map hasher.
+pub fn vm_storage_key(path: &[u8], hasher: StorageHasher) -> Vec<u8> {
+    let mut key = Vec::with_capacity(32 + 32 + path.len() + 5);
+    key.extend_from_slice(&twox_128(MODULE.as_bytes()));
+    key.extend_from_slice(&twox_128(STORAGE.as_bytes()));
+    key.extend_from_slice(&hasher.hash(&encode_bytes(path)));
+    key
+}
+
+/// Finds the hasher of the `Mvm::VMStorage` map in the SCALE encoded runtime metadata.
+/// Metadata versions 11 to 13 are supported.
+pub fn vm_storage_hasher(metadata: &[u8]) -> Result<StorageHasher> {
+    let mut reader = ScaleReader(metadata);
+    if reader.take(4)? != b"meta" {
+        return Err(anyhow!("Invalid runtime metadata"));
+    }
+    let version = reader.u8()?;
+    if !(11..=13).contains(&version) {
+        return Err(anyhow!("Unsupported runtime metadata version {}", version));
+    }
+
+    for _ in 0..reader.compact()? {
+        let name = reader.string()?;
+        let mut hasher = None;
+        if reader.u8()? == 1 {
+            reader.string()?;
+            for _ in 0..reader.compact()? {
+                let entry = reader.string()?;
+                reader.u8()?;
+                let entry_hasher = match reader.u8()? {
+                    0 => {
+                        reader.string()?;
+                        None
+                    }
+                    1 => {
+                        let hasher = StorageHasher::from_index(reader.u8()?)?;
+                        reader.string()?;
+                        reader.string()?;
+                        reader.u8()?;
+                        Some(hasher)
+                    }
+                    2 => {
+                        let hasher = StorageHasher::from_index(reader.u8()?)?;
+                        reader.strings(3)?;
+                        reader.u8()?;
+                        Some(hasher)
+                    }
+                    3 if version >= 13 => {
+                        reader.string_vec()?;
+                        let hashers = reader.compact()?;
+                        let hasher = (0..hashers)
+                            .map(|_| reader.u8().and_then(StorageHasher::from_index))
+                            .collect::<Result<Vec<_>>>()?;
+                        reader.string()?;
+                        hasher.first().copied()
+                    }
+                    ty => return Err(anyhow!("Unknown storage entry type {}", ty)),
+                };
+                reader.bytes()?;
+                reader.string_vec()?;
+                if name == MODULE && entry == STORAGE {
+                    hasher = entry_hasher;
+                }
+            }
+        }
+        if name == MODULE {
+            return hasher
+                .ok_or_else(|| anyhow!("{}::{} map not found in metadata", MODULE, STORAGE));
+        }
+
+        // calls
+        if reader.u8()? == 1 {
+            for _ in 0..reader.compact()? {
+                reader.string()?;
+                let arguments = reader.compact()?;
+                reader.strings(2 * arguments)?;
+                reader.string_vec()?;
+            }
+        }
+        // events
+        if reader.u8()? == 1 {
+            for _ in 0..reader.compact()? {
+                reader.string()?;
+                reader.string_vec()?;
+                reader.string_vec()?;
+            }
+        }
+        // constants
+        for _ in 0..reader.compact()? {
+            reader.strings(2)?;
+            reader.bytes()?;
+            reader.string_vec()?;
+        }
+        // errors
+        for _ in 0..reader.compact()? {
+            reader.string()?;
+            reader.string_vec()?;
+        }
+        if version >= 12 {
+            // module index
+            reader.u8()?;
+        }
+    }
+    Err(anyhow!("{} pallet not found in metadata", MODULE))
+}
+
+/// Reader of the SCALE encoded data.
+struct ScaleReader<'a>(&'a [u8]);
+
+impl<'a> ScaleReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let data = self.0;
+        let taken = data
+            .get(..len)
+            .ok_or_else(|| anyhow!("Unexpected end of SCALE encoded data"))?;
+        self.0 = &data[len..];
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn compact(&mut self) -> Result<usize> {
+        let (len, offset) = decode_compact(self.0)?;
+        self.take(offset)?;
+        Ok(len as usize)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.compact()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        Ok(String::from_utf8_lossy(self.bytes()?).into_owned())
+    }
+
+    fn strings(&mut self, count: usize) -> Result<()> {
+        for _ in 0..count {
+            self.bytes()?;
+        }
+        Ok(())
+    }
+
+    fn string_vec(&mut self) -> Result<()> {
+        let count = self.compact()?;
+        self.strings(count)
+    }
+}
+
+fn twox_128(data: &[u8]) -> [u8; 16] {
+    let mut hash = [0; 16];
+    hash.copy_from_slice(&twox(data, 2));
+    hash
+}
+
+/// xxHash of the data with `rounds` 64 bit hashes.
+fn twox(data: &[u8], rounds: u64) -> Vec<u8> {
+    (0..rounds)
+        .flat_map(|seed| {
+            let mut hasher = XxHash64::with_seed(seed);
+            hasher.write(data);
+            hasher.finish().to_le_bytes().to_vec()
+        })
+        .collect()
+}
+
+/// SCALE encodes bytes as a vector with the compact length prefix.
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let len = bytes.len() as u64;
+    let mut encoded = Vec::with_capacity(bytes.len() + 9);
+    if len < 1 << 6 {
+        encoded.push((len as u8) << 2);
+    } else if len < 1 << 14 {
+        encoded.extend_from_slice(&(((len as u16) << 2) | 0b01).to_le_bytes());
+    } else if len < 1 << 30 {
+        encoded.extend_from_slice(&(((len as u32) << 2) | 0b10).to_le_bytes());
+    } else {
+        let len_bytes = len.to_le_bytes();
+        let size = 8 - len.leading_zeros() as usize / 8;
+        encoded.push((((size - 4) as u8) << 2) | 0b11);
+        encoded.extend_from_slice(&len_bytes[..size]);
+    }
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+/// Decodes SCALE encoded bytes.
+fn decode_bytes(encoded: &[u8]) -> Result<Vec<u8>> {
+    let (len, offset) = decode_compact(encoded)?;
+    let bytes = &encoded[offset..];
+    if bytes.len() as u64 != len {
+        return Err(anyhow!(
+            "Invalid storage value length. Expected {} bytes, got {}",
+            len,
+            bytes.len()
+        ));
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Decodes the SCALE compact integer.
+/// Returns the integer and the length of its encoding.
+fn decode_compact(encoded: &[u8]) -> Result<(u64, usize)> {
+    let first = *encoded
+        .first()
+        .ok_or_else(|| anyhow!("Unexpected end of SCALE encoded data"))?;
+    Ok(match first & 0b11 {
+        0b00 => ((first >> 2) as u64, 1),
+        0b01 => {
+            let prefix: [u8; 2] = prefix(encoded, 2)?.try_into()?;
+            ((u16::from_le_bytes(prefix) >> 2) as u64, 2)
+        }
+        0b10 => {
+            let prefix: [u8; 4] = prefix(encoded, 4)?.try_into()?;
+            ((u32::from_le_bytes(prefix) >> 2) as u64, 4)
+        }
+        _ => {
+            let size = (first >> 2) as usize + 4;
+            if size > 8 {
+                return Err(anyhow!("Unsupported storage value length"));
+            }
+            let mut len_bytes = [0; 8];
+            len_bytes[..size].copy_from_slice(&prefix(encoded, size + 1)?[1..]);
+            (u64::from_le_bytes(len_bytes), size + 1)
+        }
+    })
+}
+
+fn prefix(encoded: &[u8], len: usize) -> Result<&[u8]> {
+    encoded
+        .get(..len)
+        .ok_or_else(|| anyhow!("Unexpected end of storage value"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use diem::prelude::*;
+
+    use crate::index::resolver::chain::loader::BytecodeLoader;
+    use crate::index::resolver::chain::substrate::{
+        decode_bytes, encode_bytes, twox_128, vm_storage_hasher, vm_storage_key, StorageHasher,
+        SubstrateBytecodeLoader,
+    };
+
+    #[test]
+    fn test_twox_128() {
+        assert_eq!(
+            hex::encode(twox_128(b"System")),
+            "26aa394eea5630e07c48ae0c9558cef7"
+        );
+    }
+
+    #[test]
+    fn test_scale_bytes() {
+        for len in &[0, 1, 63, 64, 16383, 16384] {
+            let bytes = vec![7; *len];
+            let encoded = encode_bytes(&bytes);
+            assert_eq!(decode_bytes(&encoded).unwrap(), bytes);
+        }
+        assert_eq!(encode_bytes(&[1, 2])[..1], [8]);
+        assert_eq!(encode_bytes(&[0; 64])[..2], [1, 1]);
+        assert!(decode_bytes(&[8, 1]).is_err());
+    }
+
+    fn compact(len: usize) -> Vec<u8> {
+        vec![(len as u8) << 2]
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        encode_bytes(value.as_bytes())
+    }
+
+    /// Metadata v12 with the `System` pallet and the `Mvm` pallet storage.
+    fn metadata() -> Vec<u8> {
+        let no_docs = compact(0);
+        let system = [
+            string("System"),
+            vec![0],
+            // calls
+            vec![1],
+            compact(1),
+            string("remark"),
+            compact(1),
+            string("remark"),
+            string("Vec<u8>"),
+            no_docs.clone(),
+            // events
+            vec![1],
+            compact(1),
+            string("Remarked"),
+            compact(1),
+            string("u32"),
+            no_docs.clone(),
+            // constants
+            compact(1),
+            string("Version"),
+            string("u32"),
+            encode_bytes(&[1, 0, 0, 0]),
+            no_docs.clone(),
+            // errors
+            compact(1),
+            string("Failed"),
+            no_docs.clone(),
+            // index
+            vec![0],
+        ]
+        .concat();
+        let mvm = [
+            string("Mvm"),
+            vec![1],
+            string("Mvm"),
+            compact(2),
+            string("Count"),
+            vec![0, 0],
+            string("u64"),
+            encode_bytes(&[]),
+            no_docs.clone(),
+            string("VMStorage"),
+            vec![0, 1, 5],
+            string("Vec<u8>"),
+            string("Vec<u8>"),
+            vec![0],
+            encode_bytes(&[]),
+            no_docs,
+        ]
+        .concat();
+        [b"meta".to_vec(), vec![12], compact(2), system, mvm].concat()
+    }
+
+    #[test]
+    fn test_vm_storage_hasher() {
+        assert_eq!(
+            vm_storage_hasher(&metadata()).unwrap(),
+            StorageHasher::Twox64Concat
+        );
+
+        let mut unsupported = metadata();
+        unsupported[4] = 14;
+        assert!(vm_storage_hasher(&unsupported).is_err());
+        assert!(vm_storage_hasher(&metadata()[..40]).is_err());
+    }
+
+    /// Serves JSON-RPC requests of the loader with the given storage value.
+    /// Storage is only served at the finalized block.
+    fn rpc_stand_in(expected_key: String, value: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let block = "0x1111111111111111111111111111111111111111111111111111111111111111";
+        let metadata = format!(r#""0x{}""#, hex::encode(metadata()));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let request = read_request(&mut stream);
+                let result = if request.contains("chain_getFinalizedHead") {
+                    format!(r#""{}""#, block)
+                } else if request.contains("state_getMetadata") && request.contains(block) {
+                    metadata.clone()
+                } else if request.contains("state_getStorage")
+                    && request.contains(&expected_key)
+                    && request.contains(block)
+                {
+                    value.to_owned()
+                } else {
+                    "null".to_owned()
+                };
+
+                let body = format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, result);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    /// Reads the HTTP request with its body.
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        loop {
+            let text = String::from_utf8_lossy(&request).into_owned();
+            if let Some(idx) = text.find("\r\n\r\n") {
+                let content_length = text[..idx]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_at(line.find(':')?);
+                        if name.eq_ignore_ascii_case("content-length") {
+                            value[1..].trim().parse::<usize>().ok()
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or_default();
+                if request.len() >= idx + 4 + content_length {
+                    return text;
+                }
+            }
+
+            let read = stream.read(&mut buf).unwrap();
+            if read == 0 {
+                return text;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+    }
+
+    #[test]
+    fn test_substrate_loader() {
+        let module_id = ModuleId::new(
+            AccountAddress::from_hex_literal("0x1").unwrap(),
+            Identifier::new("Coins").unwrap(),
+        );
+        let key = hex::encode(vm_storage_key(
+            &module_id.access_vector(),
+            StorageHasher::Twox64Concat,
+        ));
+
+        let url = rpc_stand_in(key.clone(), r#""0x10a11ceb0b""#);
+        let loader = SubstrateBytecodeLoader::new(url.parse().unwrap());
+        assert_eq!(
+            loader.load(module_id.clone()).unwrap(),
+            vec![0xa1, 0x1c, 0xeb, 0x0b]
+        );
+
+        let url = rpc_stand_in(key, "null");
+        let loader = SubstrateBytecodeLoader::new(url.parse().unwrap());
+        assert!(loader.load(module_id).is_err());
+    }
+}
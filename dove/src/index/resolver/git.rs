@@ -1,31 +1,42 @@
 use std::convert::TryFrom;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 
 use anyhow::Error;
-use diem::account::AccountAddress;
 use git2::{Oid, Repository};
-use git2::build::RepoBuilder;
+use git2::build::{CheckoutBuilder, RepoBuilder};
 use tiny_keccak::{Hasher, Sha3};
 
 use crate::context::Context;
 
 use crate::index::move_dir_iter;
-use lang::compiler::dialects::{DialectName};
 use crate::index::meta::{source_meta, FileMeta};
-use crate::manifest::{CheckoutParams, default_dialect, Git, MANIFEST, read_manifest};
+use crate::index::resolver::dep_settings;
+use crate::manifest::{CheckoutParams, Git};
+use crate::lock::{DoveLock, LockedGit};
 
 /// Git prefix.
 pub const PREFIX: &str = "git";
 
+/// Resolved git dependency.
+pub struct Resolved {
+    /// Path to the dependency checkout.
+    pub path: PathBuf,
+    /// Dependency with the resolved commit.
+    pub locked: LockedGit,
+    /// The checkout was moved to another commit.
+    pub updated: bool,
+}
+
 /// Returns module path by its identifier.
 /// Downloads a modules from git if it is not in the cache.
-pub fn resolve(ctx: &Context, git: &Git) -> Result<PathBuf, Error> {
+/// Checks out the commit pinned in the lock if the dependency is locked.
+pub fn resolve(ctx: &Context, git: &Git, lock: &DoveLock) -> Result<Resolved, Error> {
     let checkout_params = CheckoutParams::try_from(git)?;
 
     let deps = ctx.path_for(&ctx.manifest.layout.target_deps);
-    let repo_path = deps.join(make_local_name(&git));
+    let name = make_local_name(&git);
+    let repo_path = deps.join(&name);
 
     if !repo_path.exists() {
         if let Err(err) = checkout(checkout_params, &repo_path) {
@@ -34,7 +45,61 @@ pub fn resolve(ctx: &Context, git: &Git) -> Result<PathBuf, Error> {
         }
     }
 
-    Ok(repo_path)
+    let repo = Repository::open(&repo_path)?;
+    let updated = match lock.commit(&name) {
+        Some(commit) => checkout_commit(&repo, Oid::from_str(commit)?)?,
+        None => false,
+    };
+
+    let locked = LockedGit {
+        name,
+        git: git.git.clone(),
+        branch: git.branch.clone(),
+        tag: git.tag.clone(),
+        rev: git.rev.clone(),
+        commit: head_commit(&repo)?.to_string(),
+    };
+    Ok(Resolved {
+        path: repo_path,
+        locked,
+        updated,
+    })
+}
+
+/// Returns the commit of the repository head.
+pub fn head_commit(repo: &Repository) -> Result<Oid, Error> {
+    Ok(repo.head()?.peel_to_commit()?.id())
+}
+
+/// Checks out the given commit.
+/// Fetches the remote if the commit is not in the local repository.
+/// Returns `true` if the repository head was moved.
+pub fn checkout_commit(repo: &Repository, oid: Oid) -> Result<bool, Error> {
+    if head_commit(repo)? == oid {
+        return Ok(false);
+    }
+
+    let commit = match repo.find_commit(oid) {
+        Ok(commit) => commit,
+        Err(_) => {
+            fetch(repo, &[])?;
+            repo.find_commit(oid)
+                .map_err(|err| anyhow!("Failed to find locked commit {}: {}", oid, err))?
+        }
+    };
+
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))?;
+    repo.set_head_detached(oid)?;
+    Ok(true)
+}
+
+/// Fetches the `origin` remote of the repository.
+/// The remote default refspecs are used if `refspecs` is empty.
+pub fn fetch(repo: &Repository, refspecs: &[&str]) -> Result<(), Error> {
+    let mut remote = repo.find_remote("origin")?;
+    remote
+        .fetch(refspecs, None, None)
+        .map_err(|err| anyhow!("Failed to fetch repository :[{:?}]:{}", remote.url(), err))
 }
 
 fn checkout(params: CheckoutParams<'_>, path: &Path) -> Result<(), Error> {
@@ -96,6 +161,35 @@ fn checkout(params: CheckoutParams<'_>, path: &Path) -> Result<(), Error> {
     Ok(())
 }
 
+/// Fetches the remote of the locked dependency.
+/// Returns the latest commit of the dependency branch or tag.
+pub fn update(ctx: &Context, locked: &LockedGit) -> Result<Oid, Error> {
+    let repo_path = ctx
+        .path_for(&ctx.manifest.layout.target_deps)
+        .join(&locked.name);
+    let repo = Repository::open(&repo_path)?;
+
+    let refs = if let Some(tag) = &locked.tag {
+        let refs = format!("refs/tags/{}", tag);
+        let refspec = format!("+{}:{}", refs, refs);
+        fetch(&repo, &[refspec.as_str()])?;
+        refs
+    } else {
+        fetch(&repo, &[])?;
+        match &locked.branch {
+            Some(branch) => format!("refs/remotes/origin/{}", branch),
+            None => "refs/remotes/origin/HEAD".to_owned(),
+        }
+    };
+
+    let commit = repo
+        .find_reference(&refs)
+        .and_then(|reference| reference.resolve())
+        .and_then(|reference| reference.peel_to_commit())
+        .map_err(|err| anyhow!("Failed to resolve {} in {}: {}", refs, locked.git, err))?;
+    Ok(commit.id())
+}
+
 /// Index of git dependencies.
 pub struct GitIndex<'a> {
     ctx: &'a Context,
@@ -108,41 +202,26 @@ impl<'a> GitIndex<'a> {
         GitIndex { ctx, path }
     }
 
-    /// Returns all metadata of this `ChainIndex`.
+    /// Returns all metadata of this `GitIndex`.
     pub fn meta(&self) -> Result<Vec<FileMeta>, Error> {
-        let dep_address = get_dep_address(self.path)?;
+        // Git dependencies are parsed with the project dialect, only the address is taken
+        // from their manifest.
+        let dep_address = dep_settings(self.ctx, self.path)?;
+        let addresses = self.ctx.named_addresses()?;
 
         move_dir_iter(self.path)
-            .map(|path| source_meta(path.path(), dep_address, self.ctx.dialect.as_ref()))
+            .map(|path| {
+                source_meta(
+                    path.path(),
+                    dep_address,
+                    self.ctx.dialect.as_ref(),
+                    &addresses,
+                )
+            })
             .collect()
     }
 }
 
-fn get_dep_address(path: &Path) -> Result<Option<AccountAddress>, Error> {
-    let manifest = path.join(MANIFEST);
-    if manifest.exists() {
-        let manifest = read_manifest(&manifest)?;
-
-        let dialect_name = manifest
-            .package
-            .dialect
-            .clone()
-            .unwrap_or_else(default_dialect);
-        let dialect = DialectName::from_str(&dialect_name)?.get_dialect();
-
-        let acc_addr = manifest
-            .package
-            .account_address
-            .ok_or_else(|| anyhow!("couldn't read account address from manifest"))?;
-
-        let provided_account_address = dialect.normalize_account_address(&acc_addr)?;
-
-        Ok(Some(provided_account_address.as_account_address()))
-    } else {
-        Ok(None)
-    }
-}
-
 fn make_local_name(git: &Git) -> String {
     let mut digest = Sha3::v256();
     digest.update(git.git.as_bytes());
@@ -1,4 +1,107 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Error;
+use diem::account::AccountAddress;
+use lang::compiler::dialects::DialectName;
+use lang::disassembler::{Config, Disassembler, unit::CompiledUnit as Unit};
+
+use crate::context::Context;
+use crate::manifest::{MANIFEST, read_manifest};
+
+/// Binary dependencies resolver.
+pub mod binary;
 /// Chain dependencies resolver.
 pub mod chain;
 /// Git dependencies resolver.
 pub mod git;
+
+/// Reads account address from the dependency manifest.
+/// Returns `None` if the dependency has no manifest.
+/// Dependencies are compiled with the project dialect, so a dependency whose manifest
+/// sets another dialect is rejected.
+pub fn dep_settings(ctx: &Context, path: &Path) -> Result<Option<AccountAddress>, Error> {
+    let manifest = path.join(MANIFEST);
+    if manifest.exists() {
+        let manifest = read_manifest(&manifest)?;
+
+        if let Some(dialect_name) = &manifest.package.dialect {
+            let dialect = DialectName::from_str(dialect_name)?.get_dialect();
+            if dialect.name() != ctx.dialect.name() {
+                return Err(anyhow!(
+                    "Dependency {:?} uses the {} dialect, but the project uses the {} dialect. \
+                    Dependencies must use the dialect of the project.",
+                    path,
+                    dialect.name(),
+                    ctx.dialect.name()
+                ));
+            }
+        }
+
+        let acc_addr = manifest
+            .package
+            .account_address
+            .ok_or_else(|| anyhow!("couldn't read account address from manifest"))?;
+
+        let provided_account_address = ctx.dialect.normalize_account_address(&acc_addr)?;
+        Ok(Some(provided_account_address.as_account_address()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Generates interface source stub of the compiled module.
+pub fn interface_stub(bytecode: &[u8]) -> Result<String, Error> {
+    let config = Config {
+        light_version: true,
+    };
+    let unit = Unit::new(bytecode)?;
+    let disasm = Disassembler::new(&unit, config);
+    let source_unit = disasm.make_source_unit();
+    source_unit.code_string()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use lang::compiler::dialects::DialectName;
+
+    use crate::context::Context;
+    use crate::index::resolver::dep_settings;
+    use crate::manifest::{DoveToml, MANIFEST};
+
+    #[test]
+    fn test_dep_dialect() {
+        let dir = std::env::temp_dir().join(format!("dove_dep_{}", rand::random::<u32>()));
+        fs::create_dir_all(&dir).unwrap();
+        let ctx = Context {
+            project_dir: std::env::temp_dir(),
+            manifest: DoveToml::default(),
+            dialect: DialectName::DFinance.get_dialect(),
+            workspace_dir: None,
+            profile: None,
+        };
+
+        let manifest = dir.join(MANIFEST);
+        fs::write(&manifest, "[package]\naccount_address = \"0x1\"\n").unwrap();
+        assert!(dep_settings(&ctx, &dir).unwrap().is_some());
+
+        fs::write(
+            &manifest,
+            "[package]\naccount_address = \"0x1\"\ndialect = \"dfinance\"\n",
+        )
+        .unwrap();
+        assert!(dep_settings(&ctx, &dir).unwrap().is_some());
+
+        fs::write(
+            &manifest,
+            "[package]\naccount_address = \"0x1\"\ndialect = \"polkadot\"\n",
+        )
+        .unwrap();
+        let err = dep_settings(&ctx, &dir).unwrap_err();
+        assert!(err.to_string().contains("uses the polkadot dialect"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -33,6 +33,8 @@ pub enum SourceType {
     Git,
     /// Blockchain dependencies.
     Chain,
+    /// Compiled dependencies.
+    Binary,
 }
 
 /// Module model.
@@ -71,12 +73,14 @@ impl<'a> Index<'a> {
             Ok(Index {
                 modules,
                 dep_names,
+                local_deps: Default::default(),
                 ctx,
             })
         } else {
             Ok(Index {
                 modules: Default::default(),
                 dep_names: Default::default(),
+                local_deps: Default::default(),
                 ctx,
             })
         }
@@ -7,11 +7,17 @@ extern crate anyhow;
 #[macro_use]
 extern crate log;
 
+/// Incremental build cache.
+pub mod cache;
 /// Dove commands handler.
 pub mod cmd;
 /// Dove execution context.
 pub mod context;
 /// Dove modules index.
 pub mod index;
+/// Dependencies lock file.
+pub mod lock;
 /// Movec configuration.
 pub mod manifest;
+/// Source files watcher.
+pub mod watch;
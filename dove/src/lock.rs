@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::context::Context;
+
+/// Dove lock file name.
+pub const LOCK_FILE: &str = "Dove.lock";
+
+/// Dependencies lock.
+/// Pins every git dependency to the commit it was resolved to.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DoveLock {
+    /// Locked git dependencies.
+    #[serde(default)]
+    pub git: Vec<LockedGit>,
+}
+
+/// Locked git dependency.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockedGit {
+    /// Local name of the dependency in the external dependencies directory.
+    pub name: String,
+    /// Git url.
+    pub git: String,
+    /// Branch name.
+    pub branch: Option<String>,
+    /// Tag.
+    pub tag: Option<String>,
+    /// Commit hash from the manifest.
+    pub rev: Option<String>,
+    /// Resolved commit hash.
+    pub commit: String,
+}
+
+impl DoveLock {
+    /// Loads the lock file of the project.
+    /// Returns an empty lock if the project has no lock file.
+    pub fn load(ctx: &Context) -> Result<DoveLock, Error> {
+        let path = ctx.lock_path();
+        if path.exists() {
+            read_lock(&path)
+        } else {
+            Ok(DoveLock::default())
+        }
+    }
+
+    /// Returns the locked commit of the dependency with the given local name.
+    pub fn commit(&self, name: &str) -> Option<&str> {
+        self.git
+            .iter()
+            .find(|dep| dep.name == name)
+            .map(|dep| dep.commit.as_str())
+    }
+
+    /// Returns the mutable locked dependency with the given local name.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut LockedGit> {
+        self.git.iter_mut().find(|dep| dep.name == name)
+    }
+
+    /// Adds the resolved dependency to the lock.
+    pub fn insert(&mut self, dep: LockedGit) {
+        if !self.git.iter().any(|locked| locked.name == dep.name) {
+            self.git.push(dep);
+            self.git.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
+
+    /// Stores the lock file of the project.
+    /// An empty lock is written only if the project already has a lock file.
+    pub fn store(&self, ctx: &Context) -> Result<(), Error> {
+        let path = ctx.lock_path();
+        if self.git.is_empty() && !path.exists() {
+            return Ok(());
+        }
+
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+impl LockedGit {
+    /// Returns repository name from the git url.
+    pub fn repo_name(&self) -> &str {
+        let url = self.git.trim_end_matches('/');
+        let url = url.strip_suffix(".git").unwrap_or(url);
+        url.rsplit(&['/', ':'][..]).next().unwrap_or(url)
+    }
+
+    /// Returns `true` if the dependency is pinned to a commit in the manifest.
+    pub fn is_pinned(&self) -> bool {
+        self.rev.is_some()
+    }
+}
+
+/// Reads the lock file by path.
+pub fn read_lock(path: &Path) -> Result<DoveLock, Error> {
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lock::{DoveLock, LockedGit};
+
+    fn locked(name: &str, commit: &str) -> LockedGit {
+        LockedGit {
+            name: name.to_owned(),
+            git: "https://github.com/pontem-network/move-stdlib".to_owned(),
+            branch: Some("master".to_owned()),
+            tag: None,
+            rev: None,
+            commit: commit.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_lock_roundtrip() {
+        let mut lock = DoveLock::default();
+        lock.insert(locked("git_b", "969442fb28fc162c3e3de20ab0a3afdfa8d0f560"));
+        lock.insert(locked("git_a", "1e8d1cf3ef5b19b4ee0b7a6cd4d0a3e5c4c8f3c0"));
+        lock.insert(locked("git_a", "0000000000000000000000000000000000000000"));
+
+        assert_eq!(lock.git.len(), 2);
+        assert_eq!(lock.git[0].name, "git_a");
+        assert_eq!(
+            lock.commit("git_a"),
+            Some("1e8d1cf3ef5b19b4ee0b7a6cd4d0a3e5c4c8f3c0")
+        );
+        assert_eq!(lock.commit("git_c"), None);
+
+        assert_eq!(lock.git[0].repo_name(), "move-stdlib");
+
+        let encoded = toml::to_string(&lock).unwrap();
+        assert_eq!(lock, toml::from_str::<DoveLock>(&encoded).unwrap());
+    }
+}
@@ -1,4 +1,5 @@
 use std::{fmt, fs};
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::path::Path;
 
@@ -20,10 +21,144 @@ pub const INDEX_FILE: &str = ".Dove.man";
 #[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct DoveToml {
     /// Project info.
+    #[serde(default)]
     pub package: Package,
     /// Project layout.
     #[serde(default)]
     pub layout: Layout,
+    /// Named addresses.
+    #[serde(default)]
+    pub addresses: BTreeMap<String, String>,
+    /// Workspace members.
+    pub workspace: Option<Workspace>,
+    /// Source of the chain dependencies.
+    pub chain: Option<ChainSource>,
+    /// Build profiles.
+    #[serde(default)]
+    pub profile: BTreeMap<String, Profile>,
+    /// Linter settings.
+    #[serde(default)]
+    pub lint: Lints,
+}
+
+impl DoveToml {
+    /// Applies the profile with the given name on top of the package and layout.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), Error> {
+        let profile = self
+            .profile
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("profile '{}' is not defined in {}", name, MANIFEST))?;
+
+        if profile.account_address.is_some() {
+            self.package.account_address = profile.account_address;
+        }
+        if profile.blockchain_api.is_some() {
+            self.package.blockchain_api = profile.blockchain_api;
+        }
+
+        if !profile.layout.is_empty() {
+            let mut layout = Value::try_from(&self.layout)?;
+            let table = layout
+                .as_table_mut()
+                .ok_or_else(|| anyhow!("failed to apply profile '{}'", name))?;
+            for (key, value) in profile.layout {
+                if !table.contains_key(&key) {
+                    return Err(anyhow!(
+                        "unknown layout field '{}' in profile '{}'",
+                        key,
+                        name
+                    ));
+                }
+                table.insert(key, Value::String(value));
+            }
+            self.layout = layout.try_into()?;
+        }
+
+        self.addresses.extend(profile.addresses);
+        Ok(())
+    }
+}
+
+/// Build profile.
+/// Overrides package and layout fields for a specific environment.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Project account address.
+    pub account_address: Option<String>,
+    /// dnode base url.
+    pub blockchain_api: Option<String>,
+    /// Project layout overrides.
+    #[serde(default)]
+    pub layout: BTreeMap<String, String>,
+    /// Named addresses overrides.
+    #[serde(default)]
+    pub addresses: BTreeMap<String, String>,
+}
+
+/// Source of the chain dependencies.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "loader", rename_all = "lowercase")]
+pub enum ChainSource {
+    /// dnode REST api from the `blockchain_api`.
+    Dnode,
+    /// Local directory with `.mv` files or a chain-state snapshot file.
+    Local {
+        /// Path to the directory or the snapshot.
+        path: String,
+    },
+    /// `Mvm::VMStorage` storage of the substrate node by its JSON-RPC api from the `blockchain_api`.
+    Substrate,
+}
+
+/// Level of the lint.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// The lint is disabled.
+    Allow,
+    /// The lint is reported.
+    Warn,
+    /// The lint is reported and fails the command.
+    Deny,
+}
+
+impl Default for LintLevel {
+    fn default() -> Self {
+        LintLevel::Warn
+    }
+}
+
+/// Linter settings.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Lints {
+    /// Unused `use` imports.
+    #[serde(default)]
+    pub unused_imports: LintLevel,
+    /// Unused local variables.
+    #[serde(default)]
+    pub unused_locals: LintLevel,
+    /// Unused constants.
+    #[serde(default)]
+    pub unused_constants: LintLevel,
+    /// Abort codes given as numbers instead of `ERR_` constants.
+    #[serde(default)]
+    pub magic_abort_codes: LintLevel,
+    /// Public functions without doc comments.
+    #[serde(default)]
+    pub missing_docs: LintLevel,
+    /// Resources which are published but never moved out.
+    #[serde(default)]
+    pub unmoved_resources: LintLevel,
+}
+
+/// Workspace of several move projects.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workspace {
+    /// Paths to the workspace members.
+    #[serde(default)]
+    pub members: Vec<String>,
 }
 
 /// Project info.
@@ -44,6 +179,9 @@ pub struct Package {
     /// Dialect
     #[serde(default = "dialect")]
     pub dialect: Option<String>,
+    /// Modules which are allowed to be defined by several dependencies.
+    #[serde(default)]
+    pub allow_duplicates: Vec<String>,
 }
 
 impl Default for Package {
@@ -55,6 +193,7 @@ impl Default for Package {
             blockchain_api: None,
             dependencies: None,
             dialect: None,
+            allow_duplicates: vec![],
         }
     }
 }
@@ -261,6 +400,14 @@ pub struct DepPath {
     pub path: String,
 }
 
+/// Compiled dependencies path.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DepBinary {
+    /// Path to the `.mv` file, `.pac` package or directory with them.
+    #[serde(rename = "binary")]
+    pub path: String,
+}
+
 /// Project dependencies.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Dependencies {
@@ -275,6 +422,8 @@ pub enum Dependence {
     Git(Git),
     /// Local dependency.
     Path(DepPath),
+    /// Compiled dependency.
+    Binary(DepBinary),
 }
 
 impl<'de> Deserialize<'de> for Dependencies {
@@ -301,6 +450,10 @@ impl<'de> Deserialize<'de> for Dependencies {
                             deps.push(Dependence::Git(
                                 Git::deserialize(ele).map_err(DeError::custom)?,
                             ));
+                        } else if tbl.contains_key("binary") {
+                            deps.push(Dependence::Binary(
+                                DepBinary::deserialize(ele).map_err(DeError::custom)?,
+                            ));
                         } else {
                             deps.push(Dependence::Path(
                                 DepPath::deserialize(ele).map_err(DeError::custom)?,
@@ -329,6 +482,7 @@ impl Serialize for Dependencies {
                 .map(|dep| match dep {
                     Dependence::Path(path) => Value::try_from(path),
                     Dependence::Git(git) => Value::try_from(git),
+                    Dependence::Binary(binary) => Value::try_from(binary),
                 })
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(SerError::custom)?,
@@ -348,7 +502,10 @@ pub fn default_dialect() -> String {
 
 #[cfg(test)]
 mod test {
-    use crate::manifest::{Dependence, Dependencies, DepPath, Git, Package};
+    use crate::manifest::{
+        ChainSource, Dependence, Dependencies, DepBinary, DepPath, DoveToml, Git, LintLevel,
+        Package,
+    };
 
     fn package() -> Package {
         Package {
@@ -373,9 +530,13 @@ mod test {
                         rev: Some("969442fb28fc162c3e3de20ab0a3afdfa8d0f560".to_owned()),
                         tag: None,
                     }),
+                    Dependence::Binary(DepBinary {
+                        path: "vendor/coins.pac".to_owned(),
+                    }),
                 ],
             }),
             dialect: Some("dfinance".to_owned()),
+            allow_duplicates: vec!["0x1::Coins".to_owned()],
         }
     }
 
@@ -388,10 +549,111 @@ mod test {
                             {path = \"/stdlib\"},
                             {git = \"https://github.com/dfinance/move-stdlib\"},
                             {git = \"https://github.com/dfinance/move-stdlib\", \
-                            branch = \"master\", rev = \"969442fb28fc162c3e3de20ab0a3afdfa8d0f560\"}
+                            branch = \"master\", rev = \"969442fb28fc162c3e3de20ab0a3afdfa8d0f560\"},
+                            {binary = \"vendor/coins.pac\"}
                         ]
                         dialect= \"dfinance\"
+                        allow_duplicates = [\"0x1::Coins\"]
                         ";
         assert_eq!(package(), toml::from_str::<Package>(deps).unwrap());
     }
+
+    #[test]
+    fn parse_addresses() {
+        let manifest = "
+                        [package]
+                        name = \"Foo\"
+                        [addresses]
+                        std = \"0x1\"
+                        alice = \"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\"
+                        ";
+        let manifest = toml::from_str::<DoveToml>(manifest).unwrap();
+        assert_eq!(manifest.addresses.len(), 2);
+        assert_eq!(manifest.addresses["std"], "0x1");
+        assert_eq!(
+            manifest.addresses["alice"],
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        );
+    }
+
+    #[test]
+    fn parse_workspace() {
+        let manifest = "
+                        [workspace]
+                        members = [\"core\", \"apps/wallet\"]
+                        ";
+        let manifest = toml::from_str::<DoveToml>(manifest).unwrap();
+        assert_eq!(
+            manifest.workspace.unwrap().members,
+            vec!["core".to_owned(), "apps/wallet".to_owned()]
+        );
+        assert_eq!(manifest.package, Package::default());
+    }
+
+    #[test]
+    fn parse_lint() {
+        let manifest = "
+                        [lint]
+                        missing_docs = \"allow\"
+                        magic_abort_codes = \"deny\"
+                        ";
+        let manifest = toml::from_str::<DoveToml>(manifest).unwrap();
+        assert_eq!(manifest.lint.missing_docs, LintLevel::Allow);
+        assert_eq!(manifest.lint.magic_abort_codes, LintLevel::Deny);
+        assert_eq!(manifest.lint.unused_imports, LintLevel::Warn);
+        assert!(toml::from_str::<DoveToml>("[lint]\nunknown = \"allow\"").is_err());
+    }
+
+    #[test]
+    fn parse_chain_source() {
+        let manifest = "
+                        [chain]
+                        loader = \"local\"
+                        path = \"chain/state.json\"
+                        ";
+        let manifest = toml::from_str::<DoveToml>(manifest).unwrap();
+        assert_eq!(
+            manifest.chain,
+            Some(ChainSource::Local {
+                path: "chain/state.json".to_owned()
+            })
+        );
+
+        let manifest = toml::from_str::<DoveToml>("[chain]\nloader = \"dnode\"").unwrap();
+        assert_eq!(manifest.chain, Some(ChainSource::Dnode));
+
+        let manifest = toml::from_str::<DoveToml>("[chain]\nloader = \"substrate\"").unwrap();
+        assert_eq!(manifest.chain, Some(ChainSource::Substrate));
+    }
+
+    #[test]
+    fn apply_profile() {
+        let manifest = "
+                        [package]
+                        name = \"Foo\"
+                        account_address = \"0x1\"
+                        blockchain_api = \"http://localhost:1317\"
+                        [profile.devnet]
+                        account_address = \"0x2\"
+                        blockchain_api = \"https://devnet.example.com\"
+                        [profile.devnet.layout]
+                        module_output = \"target/devnet/modules\"
+                        [profile.broken.layout]
+                        modules = \"src\"
+                        ";
+        let manifest = toml::from_str::<DoveToml>(manifest).unwrap();
+
+        let mut devnet = manifest.clone();
+        devnet.apply_profile("devnet").unwrap();
+        assert_eq!(devnet.package.account_address.as_deref(), Some("0x2"));
+        assert_eq!(
+            devnet.package.blockchain_api.as_deref(),
+            Some("https://devnet.example.com")
+        );
+        assert_eq!(devnet.layout.module_output, "target/devnet/modules");
+        assert_eq!(devnet.layout.script_output, manifest.layout.script_output);
+
+        assert!(manifest.clone().apply_profile("testnet").is_err());
+        assert!(manifest.clone().apply_profile("broken").is_err());
+    }
 }
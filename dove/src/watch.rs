@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Error;
+
+use crate::index::{Index, move_dir_iter};
+
+/// Interval between checks of the watched files.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Time without changes after which the changes are reported.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Move files watcher.
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    files: BTreeMap<PathBuf, (Option<SystemTime>, u64)>,
+}
+
+impl Watcher {
+    /// Creates a new watcher of the move files in the given directories.
+    pub fn new(paths: Vec<PathBuf>) -> Watcher {
+        let files = snapshot(&paths);
+        Watcher { paths, files }
+    }
+
+    /// Blocks until move files are changed, added or removed.
+    /// Returns the changed files once they stop changing.
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let mut changed = self.poll();
+            if changed.is_empty() {
+                continue;
+            }
+
+            loop {
+                thread::sleep(DEBOUNCE);
+                let more = self.poll();
+                if more.is_empty() {
+                    break;
+                }
+                changed.extend(more);
+            }
+
+            changed.sort();
+            changed.dedup();
+            return changed;
+        }
+    }
+
+    /// Replaces the watched directories.
+    /// Files of the new directories are not reported as changed.
+    pub fn set_paths(&mut self, paths: Vec<PathBuf>) {
+        self.files = snapshot(&paths);
+        self.paths = paths;
+    }
+
+    fn poll(&mut self) -> Vec<PathBuf> {
+        let files = snapshot(&self.paths);
+        let mut changed = files
+            .iter()
+            .filter(|(path, state)| self.files.get(*path) != Some(*state))
+            .map(|(path, _)| path.to_owned())
+            .collect::<Vec<_>>();
+        changed.extend(
+            self.files
+                .keys()
+                .filter(|path| !files.contains_key(*path))
+                .cloned(),
+        );
+        self.files = files;
+        changed
+    }
+}
+
+/// Returns directories watched for the project:
+/// module, script and test directories and local dependencies including the transitive ones.
+pub fn source_paths(index: &Index) -> Vec<PathBuf> {
+    let ctx = index.ctx;
+    let mut paths = ctx.paths_for(&[
+        &ctx.manifest.layout.module_dir,
+        &ctx.manifest.layout.script_dir,
+        &ctx.manifest.layout.tests_dir,
+    ]);
+    paths.extend(index.local_deps.iter().cloned());
+    paths
+}
+
+/// Runs the action, then reruns it on every change of the project sources.
+/// Indexes of the projects are kept between runs and refreshed when local dependencies change.
+pub fn watch<'a, F>(indexes: &mut [Index<'a>], mut action: F) -> Result<(), Error>
+where
+    F: FnMut(&mut [Index<'a>]) -> Result<(), Error>,
+{
+    let mut watcher = Watcher::new(watched_paths(indexes));
+
+    loop {
+        if let Err(err) = action(indexes) {
+            eprintln!("error: {:?}.", err);
+        }
+        println!("Watching for changes...");
+
+        let changed = watcher.wait();
+        print_separator(&changed);
+
+        let mut is_rebuilt = false;
+        for index in indexes.iter_mut() {
+            let is_dep_changed = changed
+                .iter()
+                .any(|path| index.local_deps.iter().any(|dep| path.starts_with(dep)));
+            if is_dep_changed {
+                if let Err(err) = index.build() {
+                    eprintln!("error: {:?}.", err);
+                }
+                is_rebuilt = true;
+            }
+        }
+        if is_rebuilt {
+            // Changed dependencies may add or remove transitive local dependencies.
+            watcher.set_paths(watched_paths(indexes));
+        }
+    }
+}
+
+fn watched_paths(indexes: &[Index]) -> Vec<PathBuf> {
+    let mut paths = indexes.iter().flat_map(source_paths).collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Clears the terminal and prints the changed files.
+fn print_separator(changed: &[PathBuf]) {
+    print!("\x1B[2J\x1B[1;1H");
+    println!("{}", "-".repeat(80));
+    for path in changed {
+        println!("Changed:[{}]", path.display());
+    }
+    println!("{}", "-".repeat(80));
+}
+
+fn snapshot(paths: &[PathBuf]) -> BTreeMap<PathBuf, (Option<SystemTime>, u64)> {
+    paths
+        .iter()
+        .filter(|path| path.exists())
+        .flat_map(move_dir_iter)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((
+                entry.path().to_owned(),
+                (metadata.modified().ok(), metadata.len()),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::watch::Watcher;
+
+    #[test]
+    fn test_poll_changes() {
+        let dir = std::env::temp_dir().join(format!("dove_watch_{}", rand::random::<u32>()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.move"), "module A {}").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let mut watcher = Watcher::new(vec![dir.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(dir.join("a.move"), "module A { fun f() {} }").unwrap();
+        fs::write(dir.join("b.move"), "module B {}").unwrap();
+        fs::write(dir.join("notes.txt"), "changed").unwrap();
+        let mut changed = watcher.poll();
+        changed.sort();
+        assert_eq!(changed, vec![dir.join("a.move"), dir.join("b.move")]);
+
+        fs::remove_file(dir.join("b.move")).unwrap();
+        assert_eq!(watcher.poll(), vec![dir.join("b.move")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -8,6 +8,7 @@ use diem::move_lang::name_pool::ConstPool;
 use lang::compiler::file::MoveFile;
 use lang::compiler::file;
 use lang::compiler::dialects::DialectName;
+use lang::script_args::{parse_type_param, split_args};
 use move_executor::executor::{Executor, render_execution_result};
 use std::str::FromStr;
 
@@ -38,7 +39,13 @@ fn cli() -> App<'static, 'static> {
         )
         .arg(
             Arg::from_usage("--args [SCRIPT_ARGS]")
-                .help(r#"Number of script main() function arguments in quotes, e.g. "10 20 30""#),
+                .help(r#"Script main() function arguments in quotes, e.g. "10 [20, 30]""#),
+        )
+        .arg(
+            Arg::from_usage("-t --type [SCRIPT_TYPE_PARAMETERS]")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Script type parameter, e.g. 0x1::Dfinance::USD. \nCould be used more than once: '-t u8 -t 0x1::Dfinance::USD'"),
         )
 }
 
@@ -64,12 +71,12 @@ fn main() -> Result<()> {
 
     let dialect = cli_arguments.value_of("dialect").unwrap();
     let sender = cli_arguments.value_of("sender").unwrap();
-    let args: Vec<String> = cli_arguments
-        .value_of("args")
+    let args = split_args(cli_arguments.value_of("args").unwrap_or_default())?;
+    let type_args = cli_arguments
+        .values_of("type")
         .unwrap_or_default()
-        .split_ascii_whitespace()
-        .map(String::from)
-        .collect();
+        .map(parse_type_param)
+        .collect::<Result<Vec<_>>>()?;
 
     let dialect = DialectName::from_str(dialect)?.get_dialect();
     let sender = dialect
@@ -78,5 +85,5 @@ fn main() -> Result<()> {
 
     let executor = Executor::new(dialect.as_ref(), sender, deps);
 
-    render_execution_result(executor.execute_script(script, None, args))
+    render_execution_result(executor.execute_script(script, None, args, type_args))
 }
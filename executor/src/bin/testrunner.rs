@@ -3,6 +3,7 @@ use clap::{App, Arg};
 use std::path::PathBuf;
 use lang::compiler::{ConstPool, file};
 use move_executor::executor::{Executor, render_test_result};
+use move_executor::report::{TestFormat, TestReport, TestResult, TestSuite};
 use lang::compiler::dialects::DialectName;
 use std::str::FromStr;
 
@@ -11,6 +12,12 @@ fn cli() -> App<'static, 'static> {
         .version("0.1.0")
         .arg(Arg::from_usage("--verbose"))
         .arg(Arg::from_usage("-k --name-pattern [NAME_PATTERN]").help("Specify test name to run (or substring)"))
+        .arg(
+            Arg::from_usage("--format [FORMAT]")
+                .possible_values(&["text", "json", "junit"])
+                .default_value("text")
+                .help("Output format of the test results"),
+        )
         .arg(
             Arg::from_usage("-s --sender [SENDER_ADDRESS]")
                 .required(true)
@@ -37,6 +44,7 @@ pub fn main() -> Result<()> {
 
     let verbose_output = cli_arguments.is_present("verbose");
     let test_name_pattern = cli_arguments.value_of("name-pattern");
+    let format = TestFormat::from_str(cli_arguments.value_of("format").unwrap_or("text"))?;
 
     let modules_fpaths = cli_arguments
         .values_of("modules")
@@ -72,6 +80,7 @@ pub fn main() -> Result<()> {
 
     let executor = Executor::new(dialect.as_ref(), sender, deps);
 
+    let mut suite = TestSuite::new("tests");
     let mut has_failures = false;
     for test_file in test_files {
         let test_name = Executor::script_name(&test_file).unwrap();
@@ -82,13 +91,27 @@ pub fn main() -> Result<()> {
             }
         }
 
-        let is_test_fail =
-            render_test_result(&test_name, executor.execute_script(test_file, None, vec![]))?;
+        let result = executor.execute_script(test_file, None, vec![], vec![]);
+        let is_test_fail = if format == TestFormat::Text {
+            render_test_result(&test_name, result)?
+        } else {
+            let result = TestResult::new(&test_name, result);
+            let is_failed = result.is_failed();
+            suite.tests.push(result);
+            is_failed
+        };
         if is_test_fail {
             has_failures = true;
         }
     }
 
+    if format != TestFormat::Text {
+        let report = TestReport {
+            suites: vec![suite],
+        };
+        println!("{}", report.render(format)?);
+    }
+
     if has_failures {
         std::process::exit(1);
     }
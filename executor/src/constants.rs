@@ -33,18 +33,24 @@ fn extract_constants_from_module(
     let address = format!("{}", address);
     let module_name = module.name.value();
     for constant in constants(module) {
-        let const_name = constant.name.value();
-        if const_name.starts_with("ERR_") {
-            if let Some(val) = extract_integer_constant_value(constant) {
-                consts.insert(
-                    (address.clone(), module_name.to_owned(), val),
-                    const_name.to_owned(),
-                );
-            }
+        if let Some(val) = error_code(constant) {
+            consts.insert(
+                (address.clone(), module_name.to_owned(), val),
+                constant.name.value().to_owned(),
+            );
         }
     }
 }
 
+/// Returns the error code if the constant is an `ERR_` integer constant.
+pub fn error_code(constant: &Constant) -> Option<u128> {
+    if constant.name.value().starts_with("ERR_") {
+        extract_integer_constant_value(constant)
+    } else {
+        None
+    }
+}
+
 pub fn extract_error_constants(program: &ast::Program, consts: &mut ConstsMap) {
     let definitions = program
         .source_definitions
@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use diem::move_core_types::account_address::AccountAddress;
+use diem::move_core_types::identifier::Identifier;
+use diem::move_core_types::language_storage::ModuleId;
+use diem::move_ir_types::location::Loc;
+use diem::move_lang::compiled_unit::CompiledUnit;
+use diem::move_lang::errors::FilesSourceText;
+use diem::vm::access::ModuleAccess;
+use diem::vm::file_format::FunctionDefinitionIndex;
+
+/// Environment variable with the path of the VM execution trace.
+/// The VM appends a `<address>::<module>::<function>,<code offset>,...` line to the trace
+/// for each executed instruction. Tracing is only available in the debug build of the VM.
+const TRACE_ENV: &str = "MOVE_VM_TRACE";
+
+/// Collects the bytecode offsets executed by the VM in the executor sessions.
+pub struct Tracer {
+    path: PathBuf,
+    trace: Mutex<ExecutionTrace>,
+}
+
+impl Tracer {
+    /// Creates the tracer with the VM trace file at the given path.
+    /// The VM reads the trace path once, so a process has at most one trace file.
+    /// Returns an error in the release build, where the VM does not trace instructions.
+    pub fn new(path: &Path) -> Result<Tracer> {
+        if !cfg!(debug_assertions) {
+            return Err(anyhow!(
+                "Coverage requires the debug build of the Move VM. Build dove without --release."
+            ));
+        }
+        File::create(path)?;
+        env::set_var(TRACE_ENV, path);
+        Ok(Tracer {
+            path: path.to_owned(),
+            trace: Mutex::new(ExecutionTrace::default()),
+        })
+    }
+
+    /// Runs the VM execution and adds the offsets it executed to the trace.
+    /// Traced executions are serialized, so offsets of the parallel executions are not mixed.
+    pub fn trace<T, F: FnOnce() -> T>(&self, execute: F) -> Result<T> {
+        let mut trace = self.trace.lock().unwrap();
+        let start = fs::metadata(&self.path)?.len();
+        let result = execute();
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut executed = String::new();
+        file.read_to_string(&mut executed)?;
+        trace.merge(ExecutionTrace::parse(&executed));
+        Ok(result)
+    }
+
+    /// Returns the collected trace and starts a new one.
+    pub fn take(&self) -> ExecutionTrace {
+        std::mem::take(&mut *self.trace.lock().unwrap())
+    }
+}
+
+/// Execution counts of the module functions bytecode offsets.
+#[derive(Debug, Default)]
+pub struct ExecutionTrace {
+    hits: HashMap<ModuleId, HashMap<String, BTreeMap<u16, u64>>>,
+}
+
+impl ExecutionTrace {
+    /// Adds execution counts of another trace.
+    pub fn merge(&mut self, other: ExecutionTrace) {
+        for (module, functions) in other.hits {
+            let module = self.hits.entry(module).or_default();
+            for (function, offsets) in functions {
+                let function = module.entry(function).or_default();
+                for (offset, count) in offsets {
+                    *function.entry(offset).or_default() += count;
+                }
+            }
+        }
+    }
+
+    /// Parses the trace. Script lines and malformed lines are skipped.
+    pub fn parse(trace: &str) -> ExecutionTrace {
+        let mut result = ExecutionTrace::default();
+        for line in trace.lines() {
+            let mut parts = line.split(',');
+            let (function, offset) = match (parts.next(), parts.next()) {
+                (Some(function), Some(offset)) => (function, offset),
+                _ => continue,
+            };
+            let offset = match offset.trim().parse::<u16>() {
+                Ok(offset) => offset,
+                Err(_) => continue,
+            };
+            let segments = function.trim().split("::").collect::<Vec<_>>();
+            if segments.len() != 3 {
+                continue;
+            }
+            let address = if segments[0].starts_with("0x") {
+                AccountAddress::from_hex_literal(segments[0])
+            } else {
+                AccountAddress::from_hex_literal(&format!("0x{}", segments[0]))
+            };
+            let (address, name) = match (address, Identifier::new(segments[1])) {
+                (Ok(address), Ok(name)) => (address, name),
+                _ => continue,
+            };
+
+            *result
+                .hits
+                .entry(ModuleId::new(address, name))
+                .or_default()
+                .entry(segments[2].to_owned())
+                .or_default()
+                .entry(offset)
+                .or_default() += 1;
+        }
+        result
+    }
+
+    /// Returns execution counts of the function offsets.
+    pub fn function_hits(
+        &self,
+        module: &ModuleId,
+        function: &str,
+    ) -> Option<&BTreeMap<u16, u64>> {
+        self.hits
+            .get(module)
+            .and_then(|functions| functions.get(function))
+    }
+}
+
+/// Coverage of the module function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    pub name: String,
+    /// 1-based line of the function declaration.
+    pub line: usize,
+    /// Approximate number of the function calls: execution count of the first instruction.
+    /// Loops jumping back to the first instruction are counted as calls too.
+    pub calls: u64,
+    /// Number of the executed instructions.
+    pub covered: usize,
+    /// Number of the function instructions.
+    pub total: usize,
+}
+
+/// Coverage of the module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleCoverage {
+    pub id: ModuleId,
+    /// Path of the module source.
+    pub path: String,
+    pub functions: Vec<FunctionCoverage>,
+    /// Execution counts of the source lines with the module code.
+    pub lines: BTreeMap<usize, u64>,
+}
+
+impl ModuleCoverage {
+    /// Computes coverage of the compiled module.
+    /// Bytecode offsets are mapped to the source lines with the module source map.
+    /// Returns `None` for scripts.
+    pub fn new(
+        unit: &CompiledUnit,
+        files: &FilesSourceText,
+        trace: &ExecutionTrace,
+    ) -> Option<ModuleCoverage> {
+        let (module, source_map) = match unit {
+            CompiledUnit::Module {
+                module, source_map, ..
+            } => (module, source_map),
+            CompiledUnit::Script { .. } => return None,
+        };
+
+        let mut coverage = ModuleCoverage {
+            id: module.self_id(),
+            path: String::new(),
+            functions: vec![],
+            lines: Default::default(),
+        };
+        for (idx, def) in module.function_defs().iter().enumerate() {
+            let code = match &def.code {
+                Some(code) => code,
+                None => continue,
+            };
+            let handle = module.function_handle_at(def.function);
+            let name = module.identifier_at(handle.name).to_string();
+            let hits = trace.function_hits(&coverage.id, &name);
+            let hits_at = |offset: u16| {
+                hits.and_then(|hits| hits.get(&offset))
+                    .copied()
+                    .unwrap_or_default()
+            };
+
+            let function_map = source_map
+                .get_function_source_map(FunctionDefinitionIndex(idx as u16))
+                .ok();
+            let source = function_map
+                .and_then(|map| files.get(map.decl_location.file()))
+                .map(|source| source.as_str())
+                .unwrap_or_default();
+            if let Some(map) = function_map {
+                coverage.path = map.decl_location.file().to_owned();
+            }
+
+            let mut covered = 0;
+            for offset in 0..code.code.len() as u16 {
+                let count = hits_at(offset);
+                if count > 0 {
+                    covered += 1;
+                }
+                if let Some(loc) = function_map.and_then(|map| map.get_code_location(offset)) {
+                    let line = coverage.lines.entry(line(source, loc)).or_default();
+                    *line = (*line).max(count);
+                }
+            }
+
+            coverage.functions.push(FunctionCoverage {
+                line: function_map
+                    .map(|map| line(source, map.decl_location))
+                    .unwrap_or_default(),
+                name,
+                calls: hits_at(0),
+                covered,
+                total: code.code.len(),
+            });
+        }
+        Some(coverage)
+    }
+
+    /// Number of the executed instructions.
+    pub fn covered(&self) -> usize {
+        self.functions.iter().map(|func| func.covered).sum()
+    }
+
+    /// Number of the module instructions.
+    pub fn total(&self) -> usize {
+        self.functions.iter().map(|func| func.total).sum()
+    }
+}
+
+/// Coverage of the modules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub modules: Vec<ModuleCoverage>,
+}
+
+impl CoverageReport {
+    /// Computes coverage of the compiled modules.
+    pub fn new(
+        units: &[CompiledUnit],
+        files: &FilesSourceText,
+        trace: &ExecutionTrace,
+    ) -> CoverageReport {
+        let mut modules = units
+            .iter()
+            .filter_map(|unit| ModuleCoverage::new(unit, files, trace))
+            .collect::<Vec<_>>();
+        modules.sort_by(|a, b| a.id.cmp(&b.id));
+        CoverageReport { modules }
+    }
+
+    /// Renders the per module and per function instructions coverage.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let mut covered = 0;
+        let mut total = 0;
+        for module in &self.modules {
+            writeln!(
+                out,
+                "{}::{} {}",
+                module.id.address(),
+                module.id.name(),
+                percent(module.covered(), module.total())
+            )
+            .unwrap();
+            for func in &module.functions {
+                writeln!(
+                    out,
+                    "    {} {}",
+                    func.name,
+                    percent(func.covered, func.total)
+                )
+                .unwrap();
+            }
+            covered += module.covered();
+            total += module.total();
+        }
+        writeln!(out, "Total {}", percent(covered, total)).unwrap();
+        out
+    }
+
+    /// Renders the report in the lcov tracefile format.
+    pub fn to_lcov(&self) -> String {
+        let mut files: BTreeMap<&str, Vec<&ModuleCoverage>> = BTreeMap::new();
+        for module in &self.modules {
+            files.entry(&module.path).or_default().push(module);
+        }
+
+        let mut out = String::new();
+        for (path, modules) in files {
+            writeln!(out, "TN:").unwrap();
+            writeln!(out, "SF:{}", path).unwrap();
+            let mut lines = BTreeMap::new();
+            let mut functions = 0;
+            let mut functions_hit = 0;
+            for module in modules {
+                for func in &module.functions {
+                    let name = format!("{}::{}", module.id.name(), func.name);
+                    writeln!(out, "FN:{},{}", func.line, name).unwrap();
+                    writeln!(out, "FNDA:{},{}", func.calls, name).unwrap();
+                    functions += 1;
+                    if func.calls > 0 {
+                        functions_hit += 1;
+                    }
+                }
+                for (line, count) in &module.lines {
+                    let hits = lines.entry(*line).or_insert(0);
+                    *hits = (*hits).max(*count);
+                }
+            }
+            writeln!(out, "FNF:{}", functions).unwrap();
+            writeln!(out, "FNH:{}", functions_hit).unwrap();
+            for (line, count) in &lines {
+                writeln!(out, "DA:{},{}", line, count).unwrap();
+            }
+            writeln!(out, "LF:{}", lines.len()).unwrap();
+            writeln!(
+                out,
+                "LH:{}",
+                lines.values().filter(|count| **count > 0).count()
+            )
+            .unwrap();
+            writeln!(out, "end_of_record").unwrap();
+        }
+        out
+    }
+}
+
+/// Returns 1-based line of the location start.
+fn line(source: &str, loc: Loc) -> usize {
+    let offset = loc.span().start().to_usize().min(source.len());
+    source.as_bytes()[..offset]
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count()
+        + 1
+}
+
+fn percent(covered: usize, total: usize) -> String {
+    let percent = if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    };
+    format!("{:.2}% ({}/{})", percent, covered, total)
+}
@@ -165,6 +165,7 @@ pub fn execute_script(
     data_store: &mut FakeRemoteCache,
     script: CompiledScript,
     args: Vec<Value>,
+    ty_args: Vec<TypeTag>,
     cost_strategy: &mut CostStrategy,
     consts_map: &ConstsMap,
 ) -> Result<StepExecutionResult> {
@@ -215,7 +216,7 @@ pub fn execute_script(
         &ds,
         serialize_script(&script)?,
         args,
-        vec![],
+        ty_args,
         signers.clone(),
         cost_strategy,
     );
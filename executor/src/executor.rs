@@ -1,24 +1,32 @@
 use lang::compiler::dialects::Dialect;
-use lang::compiler::address::ProvidedAccountAddress;
+use lang::compiler::address::{ProvidedAccountAddress, NamedAddresses};
 use lang::compiler::file::MoveFile;
 use anyhow::Error;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use anyhow::anyhow;
 use anyhow::Result;
-use diem::move_core_types::parser::parse_transaction_argument;
-use diem::move_core_types::transaction_argument::TransactionArgument;
+use diem::move_core_types::language_storage::TypeTag;
 use diem::move_vm_types::values::Value;
+use diem::vm::file_format::SignatureToken;
 use crate::explain::{PipelineExecutionResult, StepExecutionResult};
-use crate::session::SessionBuilder;
+use crate::session::{CompiledDeps, SessionBuilder};
+use crate::coverage::Tracer;
 use lang::compiler::error::CompilerError;
-use diem::move_lang::errors::report_errors;
+use lang::script_args::ScriptArg;
+use diem::move_lang::errors::{report_errors, report_errors_to_buffer};
 use diem::account::AccountAddress;
 use crate::format::format_step_result;
 
 pub struct Executor<'d, 'n, 'c> {
     dialect: &'d dyn Dialect,
     sender: ProvidedAccountAddress,
+    addresses: NamedAddresses,
+    dep_senders: HashMap<String, ProvidedAccountAddress>,
     deps: Vec<MoveFile<'n, 'c>>,
+    compiled_deps: Option<CompiledDeps>,
+    tracer: Option<Arc<Tracer>>,
 }
 
 impl<'d, 'n, 'c> Executor<'d, 'n, 'c> {
@@ -30,10 +38,55 @@ impl<'d, 'n, 'c> Executor<'d, 'n, 'c> {
         Executor {
             dialect,
             sender,
+            addresses: Default::default(),
+            dep_senders: Default::default(),
             deps,
+            compiled_deps: None,
+            tracer: None,
         }
     }
 
+    pub fn with_addresses(mut self, addresses: NamedAddresses) -> Executor<'d, 'n, 'c> {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Sets the addresses of address-less modules of the dependency files.
+    pub fn with_dep_senders(
+        mut self,
+        dep_senders: HashMap<String, ProvidedAccountAddress>,
+    ) -> Executor<'d, 'n, 'c> {
+        self.dep_senders = dep_senders;
+        self
+    }
+
+    /// Compiles the dependencies once.
+    /// Scripts are then compiled with the dependencies only being type-checked.
+    pub fn compile_deps(mut self) -> Result<Executor<'d, 'n, 'c>, Error> {
+        let compiled_deps = SessionBuilder::new(self.dialect, &self.sender)
+            .with_addresses(self.addresses.clone())
+            .with_dep_senders(self.dep_senders.clone())
+            .compile_deps(&self.deps)?;
+        self.compiled_deps = Some(compiled_deps);
+        Ok(self)
+    }
+
+    /// Uses the dependencies compiled by another executor.
+    pub fn with_compiled_deps(mut self, compiled_deps: CompiledDeps) -> Executor<'d, 'n, 'c> {
+        self.compiled_deps = Some(compiled_deps);
+        self
+    }
+
+    /// Collects the executed bytecode offsets of the scripts to the tracer.
+    pub fn with_tracer(mut self, tracer: Option<Arc<Tracer>>) -> Executor<'d, 'n, 'c> {
+        self.tracer = tracer;
+        self
+    }
+
+    pub fn compiled_deps(&self) -> Option<&CompiledDeps> {
+        self.compiled_deps.as_ref()
+    }
+
     pub fn script_name(mvf: &MoveFile) -> Result<String, Error> {
         PathBuf::from(mvf.name())
             .file_name()
@@ -48,37 +101,103 @@ impl<'d, 'n, 'c> Executor<'d, 'n, 'c> {
         script: MoveFile,
         signers: Option<Vec<AccountAddress>>,
         args: Vec<String>,
+        type_args: Vec<TypeTag>,
     ) -> Result<PipelineExecutionResult, Error> {
-        let script_args = parse_script_arguments(args)?;
+        let session = if let Some(compiled_deps) = &self.compiled_deps {
+            SessionBuilder::new(self.dialect, &self.sender)
+                .with_addresses(self.addresses.clone())
+                .with_dep_senders(self.dep_senders.clone())
+                .build(&[script], &self.deps)?
+                .with_deps(compiled_deps)
+        } else {
+            let mut sources = Vec::with_capacity(self.deps.len() + 1);
+            sources.push(script);
+            sources.extend(self.deps.clone());
 
-        let mut sources = Vec::with_capacity(self.deps.len() + 1);
-        sources.push(script);
-        sources.extend(self.deps.clone());
+            SessionBuilder::new(self.dialect, &self.sender)
+                .with_addresses(self.addresses.clone())
+                .with_dep_senders(self.dep_senders.clone())
+                .build(&sources, &[])?
+        };
 
-        let session = SessionBuilder::new(self.dialect, &self.sender).build(&sources, &[])?;
-        session.execute(signers, script_args, self.dialect.cost_table())
+        let script_args = parse_script_arguments(&session.script_parameters(), args)?;
+        session.with_tracer(self.tracer.clone()).execute(
+            signers,
+            script_args,
+            type_args,
+            self.dialect.cost_table(),
+        )
     }
 }
 
-fn convert_txn_arg(arg: TransactionArgument) -> Result<Value> {
-    Ok(match arg {
-        TransactionArgument::U64(i) => Value::u64(i),
-        TransactionArgument::Address(a) => Value::address(a),
-        TransactionArgument::Bool(b) => Value::bool(b),
-        TransactionArgument::U8Vector(v) => Value::vector_u8(v),
-        _ => {
-            return Err(anyhow::Error::msg(format!(
-                "Unexpected transaction argument: {:?}",
-                arg
-            )));
-        }
+fn convert_script_arg(arg: ScriptArg) -> Value {
+    match arg {
+        ScriptArg::U8(i) => Value::u8(i),
+        ScriptArg::U64(i) => Value::u64(i),
+        ScriptArg::U128(i) => Value::u128(i),
+        ScriptArg::Bool(b) => Value::bool(b),
+        ScriptArg::Address(a) => Value::address(a),
+        ScriptArg::VectorU8(v) => Value::vector_u8(v),
+        ScriptArg::VectorU64(v) => Value::vector_u64(v),
+        ScriptArg::VectorU128(v) => Value::vector_u128(v),
+        ScriptArg::VectorBool(v) => Value::vector_bool(v),
+        ScriptArg::VectorAddress(v) => Value::vector_address(v),
+    }
+}
+
+/// Returns the type name of the script parameter as it is written in the source code.
+fn type_name(token: &SignatureToken) -> Result<String> {
+    Ok(match token {
+        SignatureToken::Bool => "bool".to_owned(),
+        SignatureToken::U8 => "u8".to_owned(),
+        SignatureToken::U64 => "u64".to_owned(),
+        SignatureToken::U128 => "u128".to_owned(),
+        SignatureToken::Address => "address".to_owned(),
+        SignatureToken::Signer => "signer".to_owned(),
+        SignatureToken::Vector(tp) => format!("vector<{}>", type_name(tp)?),
+        SignatureToken::Reference(tp) => format!("&{}", type_name(tp)?),
+        _ => return Err(anyhow!("Unexpected script parameter: {:?}", token)),
     })
 }
 
-fn parse_script_arguments(passed_args: Vec<String>) -> Result<Vec<Value>> {
-    passed_args
+/// Parses the script arguments with the types of the script parameters.
+/// Signer parameters are skipped.
+fn parse_script_arguments(
+    params: &[SignatureToken],
+    passed_args: Vec<String>,
+) -> Result<Vec<Value>> {
+    let types = params
+        .iter()
+        .map(type_name)
+        .collect::<Result<Vec<_>>>()?
         .into_iter()
-        .map(|arg| parse_transaction_argument(&arg).and_then(convert_txn_arg))
+        .filter(|tp| tp != "&signer")
+        .collect::<Vec<_>>();
+    if types.len() != passed_args.len() {
+        return Err(anyhow!(
+            "Script takes {} arguments, {} passed",
+            types.len(),
+            passed_args.len()
+        ));
+    }
+
+    types
+        .iter()
+        .zip(passed_args)
+        .enumerate()
+        .map(|(idx, (tp, arg))| {
+            ScriptArg::parse(tp, &arg)
+                .map(convert_script_arg)
+                .map_err(|err| {
+                    anyhow!(
+                        "Failed to parse argument {} [{}] of {} type: {}",
+                        arg,
+                        idx,
+                        tp,
+                        err
+                    )
+                })
+        })
         .collect()
 }
 
@@ -91,30 +210,67 @@ pub fn render_test_result(
         Err(error) => error,
     })?;
 
-    Ok(match exec_result.last() {
-        None => {
-            println!("{} ....... SCRIPT_NOT_FOUND", test_name);
-            false
+    let (output, is_err) = format_test_result(test_name, Ok(exec_result));
+    print!("{}", output);
+    Ok(is_err)
+}
+
+/// Formats the test result as `render_test_result` prints it.
+/// Compiler and execution errors are rendered to the output as failures.
+/// Returns the output and `true` if the test failed.
+pub fn format_test_result(
+    test_name: &str,
+    result: Result<PipelineExecutionResult, Error>,
+) -> (String, bool) {
+    let exec_result = match result {
+        Ok(exec_result) => exec_result,
+        Err(err) => {
+            let mut error = format_error(err);
+            if !error.ends_with('\n') {
+                error.push('\n');
+            }
+            return (
+                format!(
+                    "{} .......\n{}\n",
+                    test_name,
+                    textwrap::indent(&error, "    ")
+                ),
+                true,
+            );
         }
+    };
+
+    match exec_result.last() {
+        None => (format!("{} ....... SCRIPT_NOT_FOUND\n", test_name), false),
         Some(step_result) => match step_result {
             StepExecutionResult::Error(_) => {
-                println!("{} .......", test_name);
-
+                let mut output = format!("{} .......\n", test_name);
                 for step_result in exec_result.step_results {
-                    print!(
-                        "{}",
-                        textwrap::indent(&format_step_result(step_result, true, false), "    ",)
-                    );
+                    output.push_str(&textwrap::indent(
+                        &format_step_result(step_result, true, false),
+                        "    ",
+                    ));
                 }
-                println!();
-                true
+                output.push('\n');
+                (output, true)
             }
             StepExecutionResult::ExpectedError(_) | StepExecutionResult::Success(_) => {
-                println!("{} ....... ok", test_name);
-                false
+                (format!("{} ....... ok\n", test_name), false)
             }
         },
-    })
+    }
+}
+
+/// Renders compiler errors with their sources, other errors with their messages.
+pub fn format_error(err: Error) -> String {
+    match err.downcast::<CompilerError>() {
+        Ok(compiler_error) => String::from_utf8_lossy(&report_errors_to_buffer(
+            compiler_error.source_map,
+            compiler_error.errors,
+        ))
+        .into_owned(),
+        Err(error) => error.to_string(),
+    }
 }
 
 pub fn render_execution_result(result: Result<PipelineExecutionResult, Error>) -> Result<()> {
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+use crate::explain::PipelineExecutionResult;
+
+/// Name of the gas snapshot file in the project directory.
+pub const GAS_SNAPSHOT: &str = ".gas-snapshot";
+
+/// Gas usage of the test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestGas {
+    pub name: String,
+    /// Total gas of the test steps.
+    pub gas: u64,
+    /// Step names with the gas of the steps.
+    pub steps: Vec<(String, u64)>,
+}
+
+impl TestGas {
+    /// Collects gas usage of the script execution steps.
+    pub fn new(name: &str, result: &PipelineExecutionResult) -> TestGas {
+        TestGas {
+            name: name.to_owned(),
+            gas: result.overall_gas_spent(),
+            steps: result
+                .step_results
+                .iter()
+                .map(|(name, gas, _, _)| (name.to_owned(), *gas))
+                .collect(),
+        }
+    }
+}
+
+/// Renders gas usage of the tests and their steps as a table.
+pub fn render_gas_table(tests: &[TestGas]) -> String {
+    let mut rows = vec![("Test".to_owned(), "Step".to_owned(), "Gas".to_owned())];
+    for test in tests {
+        rows.push((test.name.to_owned(), String::new(), test.gas.to_string()));
+        for (step, gas) in &test.steps {
+            rows.push((String::new(), step.to_owned(), gas.to_string()));
+        }
+    }
+
+    let test_width = rows.iter().map(|row| row.0.len()).max().unwrap_or_default();
+    let step_width = rows.iter().map(|row| row.1.len()).max().unwrap_or_default();
+    let gas_width = rows.iter().map(|row| row.2.len()).max().unwrap_or_default();
+
+    let mut out = String::new();
+    for (test, step, gas) in rows {
+        writeln!(
+            out,
+            "{:test_width$} | {:step_width$} | {:>gas_width$}",
+            test,
+            step,
+            gas,
+            test_width = test_width,
+            step_width = step_width,
+            gas_width = gas_width
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Gas usage of the tests stored in the `.gas-snapshot` file.
+/// Each line of the file has the `<test name> (gas: <gas>)` format.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasSnapshot {
+    pub tests: BTreeMap<String, u64>,
+}
+
+/// Test with gas usage above the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasRegression {
+    pub name: String,
+    /// Gas in the snapshot.
+    pub expected: u64,
+    /// Gas of the current run.
+    pub actual: u64,
+}
+
+impl GasRegression {
+    /// Gas increase in percent.
+    pub fn percent(&self) -> f64 {
+        if self.expected == 0 {
+            100.0
+        } else {
+            (self.actual - self.expected) as f64 * 100.0 / self.expected as f64
+        }
+    }
+}
+
+impl GasSnapshot {
+    /// Creates the snapshot of the tests gas usage.
+    pub fn new(tests: &[TestGas]) -> GasSnapshot {
+        GasSnapshot {
+            tests: tests
+                .iter()
+                .map(|test| (test.name.to_owned(), test.gas))
+                .collect(),
+        }
+    }
+
+    /// Replaces gas of the given tests and keeps the other tests of the snapshot.
+    pub fn update(&mut self, tests: &[TestGas]) {
+        self.tests
+            .extend(tests.iter().map(|test| (test.name.to_owned(), test.gas)));
+    }
+
+    /// Loads the snapshot file.
+    pub fn load(path: &Path) -> Result<GasSnapshot> {
+        let snapshot = fs::read_to_string(path)
+            .map_err(|err| anyhow!("Failed to read gas snapshot {}: {}", path.display(), err))?;
+        GasSnapshot::parse(&snapshot)
+    }
+
+    /// Parses the snapshot.
+    pub fn parse(snapshot: &str) -> Result<GasSnapshot> {
+        let mut tests = BTreeMap::new();
+        for line in snapshot.lines().filter(|line| !line.trim().is_empty()) {
+            let (name, gas) = line
+                .trim()
+                .strip_suffix(')')
+                .and_then(|line| {
+                    let idx = line.rfind(" (gas: ")?;
+                    Some((&line[..idx], &line[idx + " (gas: ".len()..]))
+                })
+                .ok_or_else(|| anyhow!("Invalid gas snapshot line: {:?}", line))?;
+            let gas = gas
+                .parse::<u64>()
+                .map_err(|_| anyhow!("Invalid gas snapshot line: {:?}", line))?;
+            tests.insert(name.to_owned(), gas);
+        }
+        Ok(GasSnapshot { tests })
+    }
+
+    /// Writes the snapshot file.
+    pub fn store(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.render())?;
+        Ok(())
+    }
+
+    /// Renders the snapshot in the file format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, gas) in &self.tests {
+            writeln!(out, "{} (gas: {})", name, gas).unwrap();
+        }
+        out
+    }
+
+    /// Returns the tests whose gas rose by more than `tolerance` percent.
+    /// Tests missing in the snapshot are skipped.
+    pub fn regressions(&self, tests: &[TestGas], tolerance: f64) -> Vec<GasRegression> {
+        tests
+            .iter()
+            .filter_map(|test| {
+                let expected = *self.tests.get(&test.name)?;
+                let limit = expected as f64 * (1.0 + tolerance / 100.0);
+                if test.gas as f64 > limit {
+                    Some(GasRegression {
+                        name: test.name.to_owned(),
+                        expected,
+                        actual: test.gas,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
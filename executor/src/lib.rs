@@ -1,8 +1,11 @@
 pub mod constants;
+pub mod coverage;
 pub mod execution;
 pub mod executor;
 pub mod explain;
 pub mod format;
+pub mod gas;
 pub mod meta;
 pub mod oracles;
+pub mod report;
 pub mod session;
@@ -0,0 +1,278 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use serde::Serialize;
+
+use crate::executor::format_error;
+use crate::explain::{PipelineExecutionResult, StepExecutionResult};
+
+/// Output format of the test results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestFormat {
+    /// Human readable `name ....... ok` lines.
+    Text,
+    /// Json report.
+    Json,
+    /// JUnit XML report.
+    Junit,
+}
+
+impl FromStr for TestFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(TestFormat::Text),
+            "json" => Ok(TestFormat::Json),
+            "junit" => Ok(TestFormat::Junit),
+            _ => Err(anyhow!(
+                "Invalid format {:?}. Expected text, json or junit.",
+                s
+            )),
+        }
+    }
+}
+
+/// Status of the test or its step.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Ok,
+    Failed,
+    ExpectedError,
+    /// The test file has no script to run.
+    Skipped,
+}
+
+impl TestStatus {
+    fn of(result: &StepExecutionResult) -> TestStatus {
+        match result {
+            StepExecutionResult::Error(_) => TestStatus::Failed,
+            StepExecutionResult::ExpectedError(_) => TestStatus::ExpectedError,
+            StepExecutionResult::Success(_) => TestStatus::Ok,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            TestStatus::Ok => "ok",
+            TestStatus::Failed => "failed",
+            TestStatus::ExpectedError => "expected_error",
+            TestStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// Execution step of the test.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct StepReport {
+    pub name: String,
+    pub status: TestStatus,
+    pub gas: u64,
+    pub write_set_size: usize,
+    pub error: Option<String>,
+}
+
+/// Result of the test.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TestResult {
+    pub name: String,
+    pub status: TestStatus,
+    /// Total gas of the test steps.
+    pub gas: u64,
+    pub steps: Vec<StepReport>,
+    pub error: Option<String>,
+}
+
+impl TestResult {
+    /// Creates the test result from the script execution result.
+    /// Compiler errors are rendered to the error text.
+    /// A test without a script is skipped, as `format_test_result` does not fail it.
+    pub fn new(name: &str, result: Result<PipelineExecutionResult, Error>) -> TestResult {
+        let exec_result = match result {
+            Ok(exec_result) => exec_result,
+            Err(err) => return TestResult::failed(name, format_error(err)),
+        };
+
+        let gas = exec_result.overall_gas_spent();
+        let steps = exec_result
+            .step_results
+            .into_iter()
+            .map(|(name, gas, write_set_size, result)| StepReport {
+                name,
+                status: TestStatus::of(&result),
+                gas,
+                write_set_size,
+                error: match result {
+                    StepExecutionResult::Error(error)
+                    | StepExecutionResult::ExpectedError(error) => Some(error),
+                    StepExecutionResult::Success(_) => None,
+                },
+            })
+            .collect::<Vec<_>>();
+
+        match steps.last() {
+            None => TestResult {
+                name: name.to_owned(),
+                status: TestStatus::Skipped,
+                gas,
+                steps,
+                error: None,
+            },
+            Some(last) => TestResult {
+                name: name.to_owned(),
+                status: last.status,
+                gas,
+                error: last.error.clone(),
+                steps,
+            },
+        }
+    }
+
+    fn failed(name: &str, error: String) -> TestResult {
+        TestResult {
+            name: name.to_owned(),
+            status: TestStatus::Failed,
+            gas: 0,
+            steps: vec![],
+            error: Some(error),
+        }
+    }
+
+    /// Returns `true` if the test failed.
+    pub fn is_failed(&self) -> bool {
+        self.status == TestStatus::Failed
+    }
+}
+
+/// Tests of the project.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TestSuite {
+    pub name: String,
+    pub tests: Vec<TestResult>,
+}
+
+impl TestSuite {
+    pub fn new(name: &str) -> TestSuite {
+        TestSuite {
+            name: name.to_owned(),
+            tests: vec![],
+        }
+    }
+
+    /// Number of the failed tests.
+    pub fn failures(&self) -> usize {
+        self.tests.iter().filter(|test| test.is_failed()).count()
+    }
+
+    /// Number of the skipped tests.
+    pub fn skipped(&self) -> usize {
+        self.tests
+            .iter()
+            .filter(|test| test.status == TestStatus::Skipped)
+            .count()
+    }
+}
+
+/// Test results of the test suites.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct TestReport {
+    pub suites: Vec<TestSuite>,
+}
+
+impl TestReport {
+    /// Renders the report in the given format.
+    /// The text format is printed while the tests are running, so the empty string is returned.
+    pub fn render(&self, format: TestFormat) -> Result<String> {
+        Ok(match format {
+            TestFormat::Text => String::new(),
+            TestFormat::Json => serde_json::to_string_pretty(self)?,
+            TestFormat::Junit => self.to_junit(),
+        })
+    }
+
+    /// Renders the report in the JUnit XML format.
+    /// Execution steps with the gas and write set size are written to the `system-out` section.
+    pub fn to_junit(&self) -> String {
+        let tests = self
+            .suites
+            .iter()
+            .map(|suite| suite.tests.len())
+            .sum::<usize>();
+        let failures = self
+            .suites
+            .iter()
+            .map(|suite| suite.failures())
+            .sum::<usize>();
+
+        let mut out = String::new();
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(
+            out,
+            r#"<testsuites tests="{}" failures="{}">"#,
+            tests, failures
+        )
+        .unwrap();
+        for suite in &self.suites {
+            writeln!(
+                out,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" skipped="{}">"#,
+                escape(&suite.name),
+                suite.tests.len(),
+                suite.failures(),
+                suite.skipped()
+            )
+            .unwrap();
+            for test in &suite.tests {
+                writeln!(
+                    out,
+                    r#"    <testcase name="{}" classname="{}">"#,
+                    escape(&test.name),
+                    escape(&suite.name)
+                )
+                .unwrap();
+                if test.is_failed() {
+                    let error = test.error.as_deref().unwrap_or_default();
+                    writeln!(
+                        out,
+                        r#"      <failure message="{}">{}</failure>"#,
+                        escape(error.lines().next().unwrap_or_default()),
+                        escape(error)
+                    )
+                    .unwrap();
+                }
+                if test.status == TestStatus::Skipped {
+                    out.push_str("      <skipped message=\"Script not found\"/>\n");
+                }
+                if !test.steps.is_empty() {
+                    out.push_str("      <system-out>");
+                    for step in &test.steps {
+                        writeln!(
+                            out,
+                            "{} [status: {}, gas: {}, writeset bytes: {}]",
+                            escape(&step.name),
+                            step.status.name(),
+                            step.gas,
+                            step.write_set_size
+                        )
+                        .unwrap();
+                    }
+                    out.push_str("</system-out>\n");
+                }
+                out.push_str("    </testcase>\n");
+            }
+            out.push_str("  </testsuite>\n");
+        }
+        out.push_str("</testsuites>\n");
+        out
+    }
+}
+
+/// Escapes XML special characters.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,21 +1,25 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
 use anyhow::Error;
 use diem::move_core_types::gas_schedule::{CostTable, GasAlgebra, GasUnits};
+use diem::move_core_types::language_storage::TypeTag;
 use diem::move_ir_types::location::Loc;
 use diem::move_lang::{compiled_unit::CompiledUnit, FileCommentMap};
 use diem::move_vm_types::gas_schedule::CostStrategy;
 use diem::move_vm_types::values::Value;
 use diem::vm::CompiledModule;
-use diem::vm::file_format::CompiledScript;
+use diem::vm::access::ScriptAccess;
+use diem::vm::file_format::{CompiledScript, SignatureToken};
 
 use diem::account::AccountAddress;
+use crate::coverage::Tracer;
 use crate::execution::{execute_script, FakeRemoteCache};
 use crate::explain::PipelineExecutionResult;
 use crate::explain::StepExecutionResult;
 use crate::meta::ExecutionMeta;
-use lang::compiler::address::ProvidedAccountAddress;
-use lang::compiler::parser::{ParsingMeta, ParserArtifact};
+use lang::compiler::address::{ProvidedAccountAddress, NamedAddresses};
+use lang::compiler::parser::{set_dep_senders, ParsingMeta, ParserArtifact};
 use lang::compiler::{CompileFlow, CheckerResult, Step, compile, location};
 use diem::move_lang::errors::Errors;
 use lang::compiler::dialects::Dialect;
@@ -32,6 +36,7 @@ pub enum ExecutionUnit {
 pub struct ExecutionSession {
     units: Vec<ExecutionUnit>,
     consts: ConstsMap,
+    tracer: Option<Arc<Tracer>>,
 }
 
 impl ExecutionSession {
@@ -39,14 +44,38 @@ impl ExecutionSession {
         &self.consts
     }
 
+    /// Collects the executed bytecode offsets of the session scripts to the tracer.
+    pub fn with_tracer(mut self, tracer: Option<Arc<Tracer>>) -> ExecutionSession {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Adds precompiled dependencies to the session.
+    /// Dependency modules are published before the session units.
+    pub fn with_deps(mut self, deps: &CompiledDeps) -> ExecutionSession {
+        let mut units = deps
+            .modules
+            .iter()
+            .cloned()
+            .map(ExecutionUnit::Module)
+            .collect::<Vec<_>>();
+        units.extend(self.units);
+        self.units = units;
+        self.consts
+            .extend(deps.consts.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self
+    }
+
     pub fn execute(
         self,
         signers: Option<Vec<AccountAddress>>,
         script_args: Vec<Value>,
+        type_args: Vec<TypeTag>,
         cost_table: CostTable,
     ) -> Result<PipelineExecutionResult, Error> {
         let mut data_store = FakeRemoteCache::new(self.modules())?;
         let mut script_args = script_args;
+        let mut type_args = type_args;
 
         let mut step_results = vec![];
         for (name, script, mut meta) in self.scripts() {
@@ -57,15 +86,23 @@ impl ExecutionSession {
             let total_gas = 1_000_000;
             let mut cost_strategy =
                 CostStrategy::transaction(&cost_table, GasUnits::new(total_gas));
-            let step_result = execute_script(
-                meta,
-                &mut data_store,
-                script,
-                script_args,
-                &mut cost_strategy,
-                &self.consts,
-            )?;
+            let execute = || {
+                execute_script(
+                    meta,
+                    &mut data_store,
+                    script,
+                    script_args,
+                    type_args,
+                    &mut cost_strategy,
+                    &self.consts,
+                )
+            };
+            let step_result = match &self.tracer {
+                Some(tracer) => tracer.trace(execute)??,
+                None => execute()?,
+            };
             script_args = vec![];
+            type_args = vec![];
 
             let gas_spent = total_gas - cost_strategy.remaining_gas().get();
             let write_set_size = if let StepExecutionResult::Success(explained) = &step_result {
@@ -83,6 +120,19 @@ impl ExecutionSession {
         Ok(PipelineExecutionResult::new(step_results))
     }
 
+    /// Returns the parameter types of the first script.
+    pub fn script_parameters(&self) -> Vec<SignatureToken> {
+        self.units
+            .iter()
+            .find_map(|unit| match unit {
+                ExecutionUnit::Script((_, script, _)) => {
+                    Some(script.signature_at(script.as_inner().parameters).0.clone())
+                }
+                ExecutionUnit::Module(_) => None,
+            })
+            .unwrap_or_default()
+    }
+
     fn modules(&self) -> Vec<CompiledModule> {
         let mut modules = vec![];
         for unit in &self.units {
@@ -137,9 +187,18 @@ pub fn extract_script_doc_comments(
 
 pub type ConstsMap = BTreeMap<(String, String, u128), String>;
 
+/// Compiled modules and error constants of the dependencies.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledDeps {
+    pub modules: Vec<CompiledModule>,
+    pub consts: ConstsMap,
+}
+
 pub struct SessionBuilder<'a> {
     dialect: &'a dyn Dialect,
     sender: &'a ProvidedAccountAddress,
+    addresses: NamedAddresses,
+    dep_senders: HashMap<String, ProvidedAccountAddress>,
     loc_map: Option<BTreeMap<String, Loc>>,
     consts: ConstsMap,
 }
@@ -152,26 +211,60 @@ impl<'a> SessionBuilder<'a> {
         SessionBuilder {
             dialect,
             sender,
+            addresses: Default::default(),
+            dep_senders: Default::default(),
             loc_map: None,
             consts: Default::default(),
         }
     }
 
+    pub fn with_addresses(mut self, addresses: NamedAddresses) -> SessionBuilder<'a> {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Sets the addresses of address-less modules of the dependency files.
+    pub fn with_dep_senders(
+        mut self,
+        dep_senders: HashMap<String, ProvidedAccountAddress>,
+    ) -> SessionBuilder<'a> {
+        self.dep_senders = dep_senders;
+        self
+    }
+
     pub fn build(
         self,
         sources: &[MoveFile],
         deps: &[MoveFile],
     ) -> Result<ExecutionSession, CompilerError> {
-        compile(self.dialect, sources, deps, Some(&self.sender), self)
+        let addresses = self.addresses.clone();
+        compile(
+            self.dialect,
+            sources,
+            deps,
+            Some(&self.sender),
+            &addresses,
+            self,
+        )
+    }
+
+    /// Compiles the dependencies, so they can be reused by the sessions of several scripts.
+    pub fn compile_deps(self, deps: &[MoveFile]) -> Result<CompiledDeps, CompilerError> {
+        let session = self.build(deps, &[])?;
+        Ok(CompiledDeps {
+            modules: session.modules(),
+            consts: session.consts,
+        })
     }
 }
 
 impl<'a> CompileFlow<Result<ExecutionSession, CompilerError>> for SessionBuilder<'a> {
     fn after_parsing(
         &mut self,
-        parser_artifact: ParserArtifact,
+        mut parser_artifact: ParserArtifact,
     ) -> Step<Result<ExecutionSession, CompilerError>, ParserArtifact> {
-        if let Ok(program) = &parser_artifact.result {
+        if let Ok(program) = &mut parser_artifact.result {
+            set_dep_senders(program, &self.dep_senders);
             extract_error_constants(program, &mut self.consts)
         }
         Step::Next(parser_artifact)
@@ -252,6 +345,7 @@ impl<'a> CompileFlow<Result<ExecutionSession, CompilerError>> for SessionBuilder
         Ok(ExecutionSession {
             units: execution_units.into_iter().map(|(_, unit)| unit).collect(),
             consts: self.consts.clone(),
+            tracer: None,
         })
     }
 }
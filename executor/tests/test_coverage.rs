@@ -0,0 +1,158 @@
+use std::str::FromStr;
+
+use diem::move_core_types::account_address::AccountAddress;
+use diem::move_core_types::identifier::Identifier;
+use diem::move_core_types::language_storage::ModuleId;
+use lang::builder::{Artifacts, MoveBuilder};
+use lang::compiler::ConstPool;
+use lang::compiler::dialects::DialectName;
+use lang::compiler::file::MoveFile;
+use move_executor::coverage::{CoverageReport, ExecutionTrace};
+#[cfg(debug_assertions)]
+use move_executor::coverage::Tracer;
+#[cfg(debug_assertions)]
+use move_executor::executor::Executor;
+
+fn coverage(trace: &str) -> CoverageReport {
+    let module = r"address 0x1 {
+module Coins {
+    public fun add(a: u64, b: u64): u64 {
+        a + b
+    }
+
+    public fun sub(a: u64, b: u64): u64 {
+        a - b
+    }
+}
+}";
+    let dialect = DialectName::from_str("libra").unwrap().get_dialect();
+    let sender = dialect.normalize_account_address("0x1").unwrap();
+    let Artifacts { files, prog } = MoveBuilder::new(dialect.as_ref(), Some(&sender))
+        .build(&[MoveFile::with_content("coins.move", module)], &[]);
+    CoverageReport::new(&prog.unwrap(), &files, &ExecutionTrace::parse(trace))
+}
+
+#[test]
+fn test_function_coverage() {
+    let _pool = ConstPool::new();
+
+    let trace = (0..16)
+        .map(|offset| format!("0x1::Coins::add,{},Ret\n", offset))
+        .chain(vec!["Script::main,0,Call\n".to_owned()])
+        .collect::<String>();
+    let report = coverage(&trace);
+    assert_eq!(report.modules.len(), 1);
+
+    let module = &report.modules[0];
+    assert_eq!(module.id.name().as_str(), "Coins");
+    assert_eq!(module.path, "coins.move");
+
+    let add = &module.functions[0];
+    assert_eq!(add.name, "add");
+    assert_eq!(add.line, 3);
+    assert_eq!(add.calls, 1);
+    assert!(add.total > 0);
+    assert_eq!(add.covered, add.total);
+
+    let sub = &module.functions[1];
+    assert_eq!(sub.name, "sub");
+    assert_eq!(sub.line, 7);
+    assert_eq!(sub.calls, 0);
+    assert_eq!(sub.covered, 0);
+
+    assert_eq!(module.lines.get(&4), Some(&1));
+    assert_eq!(module.lines.get(&8), Some(&0));
+}
+
+#[test]
+fn test_lcov() {
+    let _pool = ConstPool::new();
+
+    let lcov = coverage("0x1::Coins::add,0,CopyLoc\n").to_lcov();
+    let lines = lcov.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "TN:");
+    assert_eq!(lines[1], "SF:coins.move");
+    assert!(lines.contains(&"FN:3,Coins::add"));
+    assert!(lines.contains(&"FNDA:1,Coins::add"));
+    assert!(lines.contains(&"FN:7,Coins::sub"));
+    assert!(lines.contains(&"FNDA:0,Coins::sub"));
+    assert!(lines.contains(&"FNF:2"));
+    assert!(lines.contains(&"FNH:1"));
+    assert!(lines.contains(&"DA:8,0"));
+    assert_eq!(lines.last(), Some(&"end_of_record"));
+}
+
+#[test]
+fn test_merge_trace() {
+    let _pool = ConstPool::new();
+
+    let mut trace =
+        ExecutionTrace::parse("0x1::Coins::add,0,CopyLoc\n0x1::Coins::add,1,CopyLoc\n");
+    trace.merge(ExecutionTrace::parse(
+        "0x1::Coins::add,0,CopyLoc\n0x1::Coins::sub,0,CopyLoc\n",
+    ));
+
+    let coins = ModuleId::new(
+        AccountAddress::from_hex_literal("0x1").unwrap(),
+        Identifier::new("Coins").unwrap(),
+    );
+    let add = trace.function_hits(&coins, "add").unwrap();
+    assert_eq!(add.get(&0), Some(&2));
+    assert_eq!(add.get(&1), Some(&1));
+    let sub = trace.function_hits(&coins, "sub").unwrap();
+    assert_eq!(sub.get(&0), Some(&1));
+}
+
+/// Checks that the VM writes the trace in the format the tracer parses.
+/// The VM traces instructions only in the debug build.
+#[cfg(debug_assertions)]
+#[test]
+fn test_vm_trace() {
+    let _pool = ConstPool::new();
+
+    let module = r"address 0x1 {
+module Coins {
+    public fun add(a: u64, b: u64): u64 {
+        a + b
+    }
+}
+}";
+    let script = r"script {
+    use 0x1::Coins;
+
+    fun main() {
+        assert(Coins::add(1, 2) == 3, 1);
+    }
+}";
+    let path = std::env::temp_dir().join(format!("move_trace_{}", std::process::id()));
+    let tracer = std::sync::Arc::new(Tracer::new(&path).unwrap());
+
+    let dialect = DialectName::from_str("libra").unwrap().get_dialect();
+    let sender = dialect.normalize_account_address("0x1").unwrap();
+    Executor::new(
+        dialect.as_ref(),
+        sender,
+        vec![MoveFile::with_content("coins.move", module)],
+    )
+    .with_tracer(Some(tracer.clone()))
+    .execute_script(
+        MoveFile::with_content("script.move", script),
+        None,
+        vec![],
+        vec![],
+    )
+    .unwrap()
+    .last()
+    .unwrap()
+    .effects();
+
+    let coins = ModuleId::new(
+        AccountAddress::from_hex_literal("0x1").unwrap(),
+        Identifier::new("Coins").unwrap(),
+    );
+    let trace = tracer.take();
+    let add = trace.function_hits(&coins, "add").unwrap();
+    assert_eq!(add.get(&0), Some(&1));
+    assert!(add.len() > 1);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -5,6 +5,7 @@ use resources::{assets_dir, stdlib_path, modules_path};
 use lang::compiler::error::CompilerError;
 use move_executor::executor::Executor;
 use anyhow::{Error, Context};
+use diem::move_core_types::language_storage::TypeTag;
 use lang::compiler::dialects::DialectName;
 use std::str::FromStr;
 
@@ -34,6 +35,17 @@ fn execute_script(
     dialect: &str,
     address: &str,
     args: Vec<String>,
+) -> Result<PipelineExecutionResult, Error> {
+    execute_generic_script(script, deps, dialect, address, args, vec![])
+}
+
+fn execute_generic_script(
+    script: MoveFile,
+    deps: Vec<MoveFile>,
+    dialect: &str,
+    address: &str,
+    args: Vec<String>,
+    type_args: Vec<TypeTag>,
 ) -> Result<PipelineExecutionResult, Error> {
     let dialect = DialectName::from_str(dialect)?.get_dialect();
     let sender = dialect
@@ -41,7 +53,7 @@ fn execute_script(
         .with_context(|| format!("Not a valid {:?} address: {:?}", dialect.name(), address))?;
 
     let executor = Executor::new(dialect.as_ref(), sender, deps);
-    executor.execute_script(script, None, args)
+    executor.execute_script(script, None, args, type_args)
 }
 
 #[test]
@@ -1111,3 +1123,95 @@ script {
     .unwrap()
     .effects();
 }
+
+#[test]
+fn test_execute_script_with_type_args() {
+    let _pool = ConstPool::new();
+
+    let text = r"
+script {
+    fun main<T>() {}
+}";
+
+    execute_generic_script(
+        MoveFile::with_content(script_path(), text),
+        vec![],
+        "libra",
+        "0x3",
+        vec![],
+        vec![TypeTag::U8],
+    )
+    .unwrap()
+    .last()
+    .unwrap()
+    .effects();
+
+    execute_generic_script(
+        MoveFile::with_content(script_path(), text),
+        vec![],
+        "libra",
+        "0x3",
+        vec![],
+        vec![],
+    )
+    .unwrap()
+    .last()
+    .unwrap()
+    .error();
+}
+
+#[test]
+fn test_execute_script_with_args() {
+    let _pool = ConstPool::new();
+
+    let text = r"
+script {
+    use 0x1::Vector;
+
+    fun main(_s: &signer, a: u8, b: u128, c: vector<u64>, d: vector<bool>, e: vector<address>) {
+        assert(a == 1, 1);
+        assert(b == 2, 2);
+        assert(*Vector::borrow(&c, 1) == 20, 3);
+        assert(*Vector::borrow(&d, 0), 4);
+        assert(*Vector::borrow(&e, 1) == 0x2, 5);
+    }
+}";
+
+    execute_script(
+        MoveFile::with_content(script_path(), text),
+        vec![stdlib_mod("vector.move")],
+        "libra",
+        "0x3",
+        vec![
+            "1".to_owned(),
+            "2u128".to_owned(),
+            "[10,20]".to_owned(),
+            "[true]".to_owned(),
+            "[0x1,0x2]".to_owned(),
+        ],
+    )
+    .unwrap()
+    .last()
+    .unwrap()
+    .effects();
+
+    let error = execute_script(
+        MoveFile::with_content(script_path(), text),
+        vec![stdlib_mod("vector.move")],
+        "libra",
+        "0x3",
+        vec!["1".to_owned()],
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "Script takes 5 arguments, 1 passed");
+
+    let error = execute_script(
+        MoveFile::with_content(script_path(), text),
+        vec![stdlib_mod("vector.move")],
+        "libra",
+        "0x3",
+        vec![],
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "Script takes 5 arguments, 0 passed");
+}
@@ -0,0 +1,87 @@
+use move_executor::explain::{
+    ExplainedTransactionEffects, PipelineExecutionResult, StepExecutionResult,
+};
+use move_executor::gas::{render_gas_table, GasRegression, GasSnapshot, TestGas};
+
+fn tests_gas() -> Vec<TestGas> {
+    vec![
+        TestGas::new(
+            "deposit",
+            &PipelineExecutionResult::new(vec![
+                (
+                    "main".to_owned(),
+                    100,
+                    2,
+                    StepExecutionResult::Success(ExplainedTransactionEffects::default()),
+                ),
+                (
+                    "check".to_owned(),
+                    20,
+                    0,
+                    StepExecutionResult::Success(ExplainedTransactionEffects::default()),
+                ),
+            ]),
+        ),
+        TestGas::new(
+            "withdraw",
+            &PipelineExecutionResult::new(vec![(
+                "main".to_owned(),
+                55,
+                0,
+                StepExecutionResult::Success(ExplainedTransactionEffects::default()),
+            )]),
+        ),
+    ]
+}
+
+#[test]
+fn test_gas_table() {
+    let table = render_gas_table(&tests_gas());
+    assert_eq!(
+        table.lines().collect::<Vec<_>>(),
+        vec![
+            "Test     | Step  | Gas",
+            "deposit  |       | 120",
+            "         | main  | 100",
+            "         | check |  20",
+            "withdraw |       |  55",
+            "         | main  |  55",
+        ]
+    );
+}
+
+#[test]
+fn test_gas_snapshot() {
+    let snapshot = GasSnapshot::new(&tests_gas());
+    let text = snapshot.render();
+    assert_eq!(text, "deposit (gas: 120)\nwithdraw (gas: 55)\n");
+    assert_eq!(GasSnapshot::parse(&text).unwrap(), snapshot);
+    assert!(GasSnapshot::parse("deposit 120\n").is_err());
+}
+
+#[test]
+fn test_update_gas_snapshot() {
+    let mut snapshot = GasSnapshot::parse("deposit (gas: 100)\ntransfer (gas: 70)\n").unwrap();
+    snapshot.update(&tests_gas()[..1]);
+    assert_eq!(
+        snapshot.render(),
+        "deposit (gas: 120)\ntransfer (gas: 70)\n"
+    );
+}
+
+#[test]
+fn test_gas_regressions() {
+    let snapshot = GasSnapshot::parse("deposit (gas: 100)\nwithdraw (gas: 55)\n").unwrap();
+    let tests = tests_gas();
+    assert_eq!(
+        snapshot.regressions(&tests, 0.0),
+        vec![GasRegression {
+            name: "deposit".to_owned(),
+            expected: 100,
+            actual: 120,
+        }]
+    );
+    assert_eq!(snapshot.regressions(&tests, 0.0)[0].percent(), 20.0);
+    assert!(snapshot.regressions(&tests, 20.0).is_empty());
+    assert!(GasSnapshot::default().regressions(&tests, 0.0).is_empty());
+}
@@ -0,0 +1,79 @@
+use anyhow::anyhow;
+
+use move_executor::explain::{
+    ExplainedTransactionEffects, PipelineExecutionResult, StepExecutionResult,
+};
+use move_executor::report::{TestReport, TestResult, TestStatus, TestSuite};
+
+fn report() -> TestReport {
+    let mut suite = TestSuite::new("coins");
+    suite.tests.push(TestResult::new(
+        "deposit",
+        Ok(PipelineExecutionResult::new(vec![
+            (
+                "main".to_owned(),
+                10,
+                2,
+                StepExecutionResult::Success(ExplainedTransactionEffects::default()),
+            ),
+            (
+                "check".to_owned(),
+                5,
+                0,
+                StepExecutionResult::with_expected_error("abort 1".to_owned()),
+            ),
+        ])),
+    ));
+    suite.tests.push(TestResult::new(
+        "withdraw",
+        Ok(PipelineExecutionResult::new(vec![(
+            "main".to_owned(),
+            7,
+            0,
+            StepExecutionResult::with_error("Execution aborted with code <1>".to_owned()),
+        )])),
+    ));
+    suite
+        .tests
+        .push(TestResult::new("broken", Err(anyhow!("Failed to load"))));
+    suite.tests.push(TestResult::new(
+        "empty",
+        Ok(PipelineExecutionResult::new(vec![])),
+    ));
+    TestReport {
+        suites: vec![suite],
+    }
+}
+
+#[test]
+fn test_result_status() {
+    let report = report();
+    let tests = &report.suites[0].tests;
+    assert_eq!(tests[0].status, TestStatus::ExpectedError);
+    assert_eq!(tests[0].gas, 15);
+    assert_eq!(tests[0].steps[0].write_set_size, 2);
+    assert_eq!(tests[1].status, TestStatus::Failed);
+    assert_eq!(tests[2].status, TestStatus::Failed);
+    assert_eq!(tests[2].error.as_deref(), Some("Failed to load"));
+    assert_eq!(tests[3].status, TestStatus::Skipped);
+    assert!(!tests[3].is_failed());
+    assert_eq!(report.suites[0].failures(), 2);
+    assert_eq!(report.suites[0].skipped(), 1);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["suites"][0]["tests"][0]["status"], "expected_error");
+    assert_eq!(json["suites"][0]["tests"][0]["steps"][1]["gas"], 5);
+}
+
+#[test]
+fn test_junit() {
+    let junit = report().to_junit();
+    assert!(junit.contains(r#"<testsuites tests="4" failures="2">"#));
+    assert!(junit.contains(r#"<testsuite name="coins" tests="4" failures="2" skipped="1">"#));
+    assert!(junit.contains(r#"<skipped message="Script not found"/>"#));
+    assert!(junit.contains(r#"<testcase name="deposit" classname="coins">"#));
+    assert!(junit.contains("main [status: ok, gas: 10, writeset bytes: 2]"));
+    assert!(junit.contains(
+        r#"<failure message="Execution aborted with code &lt;1&gt;">Execution aborted with code &lt;1&gt;</failure>"#
+    ));
+}
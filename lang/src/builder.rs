@@ -1,10 +1,11 @@
 use crate::compiler::dialects::Dialect;
-use crate::compiler::address::ProvidedAccountAddress;
+use crate::compiler::address::{ProvidedAccountAddress, NamedAddresses};
 use crate::compiler::file::MoveFile;
-use crate::compiler::{CompileFlow, compile};
-use crate::compiler::parser::ParsingMeta;
+use crate::compiler::{CompileFlow, compile, Step};
+use crate::compiler::parser::{set_dep_senders, ParserArtifact, ParsingMeta};
 use diem::move_lang::compiled_unit::CompiledUnit;
 use diem::move_lang::errors::{Errors, FilesSourceText};
+use std::collections::HashMap;
 
 pub struct Artifacts {
     pub files: FilesSourceText,
@@ -14,6 +15,8 @@ pub struct Artifacts {
 pub struct MoveBuilder<'a> {
     dialect: &'a dyn Dialect,
     sender: Option<&'a ProvidedAccountAddress>,
+    addresses: NamedAddresses,
+    dep_senders: HashMap<String, ProvidedAccountAddress>,
 }
 
 impl<'a> MoveBuilder<'a> {
@@ -21,15 +24,43 @@ impl<'a> MoveBuilder<'a> {
         dialect: &'a dyn Dialect,
         sender: Option<&'a ProvidedAccountAddress>,
     ) -> MoveBuilder<'a> {
-        MoveBuilder { dialect, sender }
+        MoveBuilder {
+            dialect,
+            sender,
+            addresses: Default::default(),
+            dep_senders: Default::default(),
+        }
+    }
+
+    pub fn with_addresses(mut self, addresses: NamedAddresses) -> MoveBuilder<'a> {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Sets the addresses of address-less modules of the dependency files.
+    /// Modules of the files missing in the map are placed at the sender address.
+    pub fn with_dep_senders(
+        mut self,
+        dep_senders: HashMap<String, ProvidedAccountAddress>,
+    ) -> MoveBuilder<'a> {
+        self.dep_senders = dep_senders;
+        self
     }
 
     pub fn build(self, targets: &[MoveFile], deps: &[MoveFile]) -> Artifacts {
-        compile(self.dialect, targets, deps, self.sender, self)
+        let addresses = self.addresses.clone();
+        compile(self.dialect, targets, deps, self.sender, &addresses, self)
     }
 }
 
 impl<'a> CompileFlow<Artifacts> for MoveBuilder<'a> {
+    fn after_parsing(&mut self, mut artifact: ParserArtifact) -> Step<Artifacts, ParserArtifact> {
+        if let Ok(program) = &mut artifact.result {
+            set_dep_senders(program, &self.dep_senders);
+        }
+        Step::Next(artifact)
+    }
+
     fn after_translate(
         &mut self,
         meta: ParsingMeta,
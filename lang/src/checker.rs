@@ -1,14 +1,17 @@
 use crate::compiler::dialects::Dialect;
-use crate::compiler::address::ProvidedAccountAddress;
+use crate::compiler::address::{ProvidedAccountAddress, NamedAddresses};
 use crate::compiler::file::MoveFile;
 use crate::compiler::{CompileFlow, Step, compile, CheckerResult};
-use crate::compiler::parser::{ParsingMeta, ParserArtifact};
+use crate::compiler::parser::{set_dep_senders, ParsingMeta, ParserArtifact};
 use diem::move_lang::compiled_unit::CompiledUnit;
 use diem::move_lang::errors::Errors;
+use std::collections::HashMap;
 
 pub struct MoveChecker<'a> {
     dialect: &'a dyn Dialect,
     sender: Option<&'a ProvidedAccountAddress>,
+    addresses: NamedAddresses,
+    dep_senders: HashMap<String, ProvidedAccountAddress>,
 }
 
 impl<'a> MoveChecker<'a> {
@@ -16,19 +19,43 @@ impl<'a> MoveChecker<'a> {
         dialect: &'a dyn Dialect,
         sender: Option<&'a ProvidedAccountAddress>,
     ) -> MoveChecker<'a> {
-        MoveChecker { dialect, sender }
+        MoveChecker {
+            dialect,
+            sender,
+            addresses: Default::default(),
+            dep_senders: Default::default(),
+        }
+    }
+
+    pub fn with_addresses(mut self, addresses: NamedAddresses) -> MoveChecker<'a> {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Sets the addresses of address-less modules of the dependency files.
+    /// Modules of the files missing in the map are placed at the sender address.
+    pub fn with_dep_senders(
+        mut self,
+        dep_senders: HashMap<String, ProvidedAccountAddress>,
+    ) -> MoveChecker<'a> {
+        self.dep_senders = dep_senders;
+        self
     }
 
     pub fn check(self, targets: &[MoveFile], deps: &[MoveFile]) -> Result<(), Errors> {
-        compile(self.dialect, targets, deps, self.sender, self)
+        let addresses = self.addresses.clone();
+        compile(self.dialect, targets, deps, self.sender, &addresses, self)
     }
 }
 
 impl<'a> CompileFlow<Result<(), Errors>> for MoveChecker<'a> {
     fn after_parsing(
         &mut self,
-        parser_artifact: ParserArtifact,
+        mut parser_artifact: ParserArtifact,
     ) -> Step<Result<(), Errors>, ParserArtifact> {
+        if let Ok(program) = &mut parser_artifact.result {
+            set_dep_senders(program, &self.dep_senders);
+        }
         if parser_artifact.result.is_err() {
             let ParserArtifact { meta, result } = parser_artifact;
             Step::Stop(
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use diem::move_lang::shared::Address;
 use diem::move_core_types::account_address::AccountAddress;
 
@@ -77,3 +77,27 @@ impl AddressMap {
             .collect()
     }
 }
+
+/// Named account addresses, substituted for `{{name}}` placeholders in source code.
+#[derive(Default, Debug, Clone)]
+pub struct NamedAddresses {
+    addresses: BTreeMap<String, ProvidedAccountAddress>,
+}
+
+impl NamedAddresses {
+    pub fn insert(&mut self, name: String, address: ProvidedAccountAddress) {
+        self.addresses.insert(name, address);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ProvidedAccountAddress> {
+        self.addresses.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ProvidedAccountAddress)> {
+        self.addresses.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+}
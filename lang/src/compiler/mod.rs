@@ -6,7 +6,7 @@ pub use diem::move_lang::name_pool::ConstPool;
 
 use parser::parse_program;
 
-use crate::compiler::address::ProvidedAccountAddress;
+use crate::compiler::address::{ProvidedAccountAddress, NamedAddresses};
 use crate::compiler::dialects::Dialect;
 use crate::compiler::file::MoveFile;
 use crate::compiler::parser::{ParserArtifact, ParsingMeta};
@@ -52,13 +52,15 @@ pub fn compile<A>(
     targets: &[MoveFile],
     deps: &[MoveFile],
     sender: Option<&ProvidedAccountAddress>,
+    addresses: &NamedAddresses,
     mut flow: impl CompileFlow<A>,
 ) -> A {
     flow.init(dialect, &sender);
-    let parser_result = match flow.after_parsing(parse_program(dialect, targets, deps, sender)) {
-        Step::Stop(artifact) => return artifact,
-        Step::Next(res) => res,
-    };
+    let parser_result =
+        match flow.after_parsing(parse_program(dialect, targets, deps, sender, addresses)) {
+            Step::Stop(artifact) => return artifact,
+            Step::Next(res) => res,
+        };
     let ParserArtifact {
         meta,
         result: pprog_res,
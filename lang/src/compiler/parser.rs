@@ -3,10 +3,11 @@ use diem::move_lang::{strip_comments_and_verify, FileCommentMap, parser};
 
 use diem::move_lang::name_pool::ConstPool;
 use std::collections::{HashMap, BTreeMap};
+use diem::move_lang::parser::ast::Definition;
 use diem::move_lang::parser::syntax::parse_file_string;
 use crate::compiler::source_map::{FileOffsetMap, ProjectOffsetMap, len_difference};
 use crate::compiler::dialects::{Dialect, line_endings};
-use crate::compiler::address::ProvidedAccountAddress;
+use crate::compiler::address::{ProvidedAccountAddress, NamedAddresses};
 use crate::compiler::file::MoveFile;
 use diem::move_lang::errors::{FilesSourceText, Errors};
 
@@ -28,6 +29,7 @@ pub fn parse_program(
     targets: &[MoveFile],
     deps: &[MoveFile],
     sender: Option<&ProvidedAccountAddress>,
+    addresses: &NamedAddresses,
 ) -> ParserArtifact {
     let mut files: FilesSourceText = HashMap::new();
     let mut source_definitions = Vec::new();
@@ -39,8 +41,14 @@ pub fn parse_program(
 
     for target in targets {
         let name = ConstPool::push(target.name());
-        let (defs, comments, es, offsets_map) =
-            parse_file(dialect, &mut files, name, target.content(), sender);
+        let (defs, comments, es, offsets_map) = parse_file(
+            dialect,
+            &mut files,
+            name,
+            target.content(),
+            sender,
+            addresses,
+        );
         source_definitions.extend(defs);
         comment_map.insert(name, comments);
         project_offsets_map.0.insert(name, offsets_map);
@@ -50,7 +58,7 @@ pub fn parse_program(
     for dep in deps {
         let name = ConstPool::push(&dep.name());
         let (defs, _, es, offsets_map) =
-            parse_file(dialect, &mut files, name, dep.content(), sender);
+            parse_file(dialect, &mut files, name, dep.content(), sender, addresses);
         project_offsets_map.0.insert(name, offsets_map);
         lib_definitions.extend(defs);
         errors.extend(es);
@@ -81,13 +89,15 @@ pub fn parse_file(
     fname: &'static str,
     source_buffer: &str,
     sender: Option<&ProvidedAccountAddress>,
+    addresses: &NamedAddresses,
 ) -> (
     Vec<parser::ast::Definition>,
     FileCommentMap,
     Errors,
     FileOffsetMap,
 ) {
-    let (source_buffer, file_source_map) = normalize_source_text(dialect, source_buffer, sender);
+    let (source_buffer, file_source_map) =
+        normalize_source_text(dialect, source_buffer, sender, addresses);
     let (no_comments_buffer, comment_map) = match strip_comments_and_verify(fname, &source_buffer)
     {
         Err(errors) => {
@@ -104,10 +114,36 @@ pub fn parse_file(
     }
 }
 
+/// Places address-less modules of the files at the addresses from `dep_senders`.
+/// Modules of the files missing in the map stay at the sender address.
+pub fn set_dep_senders(
+    program: &mut parser::ast::Program,
+    dep_senders: &HashMap<String, ProvidedAccountAddress>,
+) {
+    if dep_senders.is_empty() {
+        return;
+    }
+
+    let with_sender = |def: Definition| match def {
+        Definition::Module(module) => match dep_senders.get(module.loc.file()) {
+            Some(sender) => Definition::Address(module.loc, sender.as_address(), vec![module]),
+            None => Definition::Module(module),
+        },
+        def => def,
+    };
+    program.source_definitions = program
+        .source_definitions
+        .drain(..)
+        .map(with_sender)
+        .collect();
+    program.lib_definitions = program.lib_definitions.drain(..).map(with_sender).collect();
+}
+
 fn normalize_source_text(
     dialect: &dyn Dialect,
     source_text: &str,
     sender: Option<&ProvidedAccountAddress>,
+    addresses: &NamedAddresses,
 ) -> (String, FileOffsetMap) {
     let (mut source_text, mut file_source_map) = line_endings::normalize(source_text);
     if let Some(sender) = sender {
@@ -117,6 +153,14 @@ fn normalize_source_text(
             &mut file_source_map,
         );
     }
+    for (name, address) in addresses.iter() {
+        source_text = replace_address_placeholder(
+            source_text,
+            name,
+            &address.normalized_original,
+            &mut file_source_map,
+        );
+    }
     source_text = dialect.replace_addresses(&source_text, &mut file_source_map);
     (source_text, file_source_map)
 }
@@ -132,12 +176,112 @@ fn replace_sender_placeholder(
         "Sender address length is too short: {}",
         sender.len()
     );
+    replace_address_placeholder(s, "sender", sender, file_source_map)
+}
+
+/// replace {{name}} and {{ name }} inside source code
+fn replace_address_placeholder(
+    s: String,
+    name: &str,
+    address: &str,
+    file_source_map: &mut FileOffsetMap,
+) -> String {
     let mut new_s = s;
-    for template in &["{{sender}}", "{{ sender }}"] {
-        while let Some(pos) = new_s.find(template) {
-            new_s.replace_range(pos..pos + template.len(), sender);
-            file_source_map.insert_layer(pos + sender.len(), len_difference(template, sender));
+    for template in &[format!("{{{{{}}}}}", name), format!("{{{{ {} }}}}", name)] {
+        while let Some(pos) = new_s.find(template.as_str()) {
+            new_s.replace_range(pos..pos + template.len(), address);
+            file_source_map.insert_layer(pos + address.len(), len_difference(template, address));
         }
     }
     new_s
 }
+
+#[cfg(test)]
+mod test {
+    use crate::compiler::address::NamedAddresses;
+    use crate::compiler::dialects::DialectName;
+    use super::{normalize_source_text, parse_file, set_dep_senders};
+    use codespan::{ByteIndex, Span};
+    use diem::move_lang::name_pool::ConstPool;
+    use diem::move_lang::parser::ast::{Definition, Program};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_replace_named_addresses() {
+        let dialect = DialectName::DFinance.get_dialect();
+        let mut addresses = NamedAddresses::default();
+        addresses.insert(
+            "std".to_owned(),
+            dialect.normalize_account_address("0x1").unwrap(),
+        );
+
+        let source = "script { use {{std}}::Event; use {{ std }}::Math; fun main() {} }";
+        let (normalized, offsets) =
+            normalize_source_text(dialect.as_ref(), source, None, &addresses);
+
+        let std = &addresses.get("std").unwrap().normalized_original;
+        assert_eq!(
+            normalized,
+            format!(
+                "script {{ use {}::Event; use {}::Math; fun main() {{}} }}",
+                std, std
+            )
+        );
+
+        let main = normalized.find("main").unwrap();
+        let span = offsets.translate_span(Span::new(
+            ByteIndex(main as u32),
+            ByteIndex((main + 4) as u32),
+        ));
+        assert_eq!(span.start().to_usize(), source.find("main").unwrap());
+        assert_eq!(span.end().to_usize(), source.find("main").unwrap() + 4);
+    }
+
+    #[test]
+    fn test_set_dep_senders() {
+        let _pool = ConstPool::new();
+        let dialect = DialectName::DFinance.get_dialect();
+        let sender = dialect.normalize_account_address("0x1").unwrap();
+        let dep_sender = dialect.normalize_account_address("0x2").unwrap();
+
+        let mut files = HashMap::new();
+        let mut parse = |name: &str| {
+            let name = ConstPool::push(name);
+            let source = "module M {} address 0x3 { module N {} }";
+            let (defs, _, errors, _) = parse_file(
+                dialect.as_ref(),
+                &mut files,
+                name,
+                source,
+                Some(&sender),
+                &NamedAddresses::default(),
+            );
+            assert!(errors.is_empty());
+            defs
+        };
+        let mut program = Program {
+            source_definitions: parse("project.move"),
+            lib_definitions: parse("dep.move"),
+        };
+
+        let mut dep_senders = HashMap::new();
+        dep_senders.insert("dep.move".to_owned(), dep_sender.clone());
+        set_dep_senders(&mut program, &dep_senders);
+
+        assert!(matches!(
+            program.source_definitions[0],
+            Definition::Module(_)
+        ));
+        match &program.lib_definitions[0] {
+            Definition::Address(_, address, modules) => {
+                assert_eq!(*address, dep_sender.as_address());
+                assert_eq!(modules[0].name.0.value, "M");
+            }
+            _ => panic!("Expected address definition"),
+        }
+        match &program.lib_definitions[1] {
+            Definition::Address(_, address, _) => assert_ne!(*address, dep_sender.as_address()),
+            _ => panic!("Expected address definition"),
+        }
+    }
+}
@@ -9,3 +9,4 @@ pub mod builder;
 pub mod checker;
 pub mod compiler;
 pub mod meta_extractor;
+pub mod script_args;
@@ -7,7 +7,7 @@ use crate::compiler::error::CompilerError;
 use crate::compiler::dialects::Dialect;
 use crate::compiler::file::MoveFile;
 use diem::move_lang::parser::ast::{Script, Type, Type_, ModuleAccess_};
-use crate::compiler::address::ProvidedAccountAddress;
+use crate::compiler::address::{ProvidedAccountAddress, NamedAddresses};
 
 pub struct ScriptMetadata;
 
@@ -15,9 +15,17 @@ impl ScriptMetadata {
     pub fn extract(
         dialect: &dyn Dialect,
         sender: Option<&ProvidedAccountAddress>,
+        addresses: &NamedAddresses,
         script: &MoveFile,
     ) -> Result<Vec<Meta>, Error> {
-        compile(dialect, &[script.to_owned()], &[], sender, ScriptMetadata)
+        compile(
+            dialect,
+            &[script.to_owned()],
+            &[],
+            sender,
+            addresses,
+            ScriptMetadata,
+        )
     }
 }
 
@@ -0,0 +1,459 @@
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use diem::move_core_types::account_address::AccountAddress;
+use diem::move_core_types::identifier::Identifier;
+use diem::move_core_types::language_storage::{StructTag, TypeTag};
+use diem::move_lang::parser::ast::{ModuleAccess_, ModuleIdent_, Type, Type_};
+use diem::move_lang::parser::lexer::{Lexer, Tok};
+use diem::move_lang::parser::syntax::parse_type;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::compiler::bech32::{bech32_into_libra, HRP};
+use crate::compiler::ss58::ss58_to_libra;
+
+lazy_static! {
+    static ref WORD_REGEX: Regex = Regex::new(r"[0-9A-Za-z]+").unwrap();
+}
+
+/// Replaces ss58 and bech32 addresses with the hex addresses.
+pub fn replace_addresses(text: &str) -> String {
+    WORD_REGEX
+        .replace_all(text, |caps: &Captures| {
+            let word = &caps[0];
+            ss58_to_libra(word)
+                .or_else(|err| {
+                    if word.starts_with(HRP) {
+                        bech32_into_libra(word)
+                    } else {
+                        Err(err)
+                    }
+                })
+                .unwrap_or_else(|_| word.to_owned())
+        })
+        .into_owned()
+}
+
+/// Parses the script type argument, e.g. `0x1::Coins::USD`.
+/// Type arguments may contain ss58 and bech32 addresses.
+pub fn parse_type_param(tp: &str) -> Result<TypeTag> {
+    let map_err = |err| Error::msg(format!("{:?}", err));
+
+    let tp = replace_addresses(tp);
+    let mut lexer = Lexer::new(&tp, "tp", Default::default());
+    lexer.advance().map_err(map_err)?;
+    let type_tag = parse_type_params(&mut lexer)?;
+    if lexer.peek() != Tok::EOF {
+        return Err(anyhow!(
+            "Invalid type parameter {:?}. Unexpected token '{}'",
+            tp,
+            lexer.content()
+        ));
+    }
+    Ok(type_tag)
+}
+
+/// Parses the type at the lexer position.
+pub fn parse_type_params(lexer: &mut Lexer) -> Result<TypeTag> {
+    let ty = parse_type(lexer).map_err(|err| Error::msg(format!("{:?}", err)))?;
+    unwrap_spanned_ty(ty)
+}
+
+/// Converts the parsed type to the type tag.
+pub fn unwrap_spanned_ty(ty: Type) -> Result<TypeTag, Error> {
+    fn unwrap_spanned_ty_(ty: Type, this: Option<AccountAddress>) -> Result<TypeTag, Error> {
+        let st = match ty.value {
+            Type_::Apply(ma, mut ty_params) => {
+                match (ma.value, this) {
+                    // N
+                    (ModuleAccess_::Name(name), this) => match name.value.as_ref() {
+                        "bool" => TypeTag::Bool,
+                        "u8" => TypeTag::U8,
+                        "u64" => TypeTag::U64,
+                        "u128" => TypeTag::U128,
+                        "address" => TypeTag::Address,
+                        "signer" => TypeTag::Signer,
+                        "Vec" | "vector" if ty_params.len() == 1 => TypeTag::Vector(
+                            unwrap_spanned_ty_(ty_params.pop().unwrap(), this)?.into(),
+                        ),
+                        _ => bail!(
+                            "Could not parse input: type without struct name & module address"
+                        ),
+                    },
+                    // M.S
+                    (ModuleAccess_::ModuleAccess(_module, _struct_name), None) => {
+                        bail!("Could not parse input: type without module address");
+                    }
+                    // M.S + parent address
+                    (ModuleAccess_::ModuleAccess(name, struct_name), Some(this)) => {
+                        TypeTag::Struct(StructTag {
+                            address: this,
+                            module: Identifier::new(name.0.value)?,
+                            name: Identifier::new(struct_name.value)?,
+                            type_params: ty_params
+                                .into_iter()
+                                .map(|ty| unwrap_spanned_ty_(ty, Some(this)))
+                                .collect::<Result<_>>()?,
+                        })
+                    }
+
+                    // OxADDR.M.S
+                    (ModuleAccess_::QualifiedModuleAccess(module_id, struct_name), _) => {
+                        let ModuleIdent_ { name, address } = module_id.0.value;
+                        let address = AccountAddress::new(address.to_u8());
+                        TypeTag::Struct(StructTag {
+                            address,
+                            module: Identifier::new(name.0.value)?,
+                            name: Identifier::new(struct_name.value)?,
+                            type_params: ty_params
+                                .into_iter()
+                                .map(|ty| unwrap_spanned_ty_(ty, Some(address)))
+                                .collect::<Result<_>>()?,
+                        })
+                    }
+                }
+            }
+            _ => {
+                bail!("Could not parse input: unsupported type");
+            }
+        };
+
+        Ok(st)
+    }
+
+    unwrap_spanned_ty_(ty, None)
+}
+
+/// Script argument type.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, PartialOrd)]
+pub enum ScriptArg {
+    /// u8
+    U8(u8),
+    /// u64
+    U64(u64),
+    /// u128
+    U128(u128),
+    /// bool
+    Bool(bool),
+    /// address
+    Address(AccountAddress),
+    /// vector<u8>
+    VectorU8(Vec<u8>),
+    /// vector<u64>
+    VectorU64(Vec<u64>),
+    /// vector<u128>
+    VectorU128(Vec<u128>),
+    /// vector<bool>
+    VectorBool(Vec<bool>),
+    /// vector<address>
+    VectorAddress(Vec<AccountAddress>),
+}
+
+impl ScriptArg {
+    /// Parses the argument of the script parameter type, e.g. `u64` or `vector<address>`.
+    /// Numbers may have the type suffix, e.g. `10u8`.
+    /// Vectors are passed in the `[n1, n2, ..., nn]` format,
+    /// `vector<u8>` may also be a hex string: `68656c6c6f` or `x"68656c6c6f"`,
+    /// or a byte string: `b"hello"`.
+    /// Addresses may be ss58 and bech32 addresses.
+    pub fn parse(tp: &str, value: &str) -> Result<ScriptArg> {
+        let number = value.strip_suffix(tp).unwrap_or(value);
+        Ok(match tp {
+            "bool" => ScriptArg::Bool(value.parse()?),
+            "u8" => ScriptArg::U8(number.parse()?),
+            "u64" => ScriptArg::U64(number.parse()?),
+            "u128" => ScriptArg::U128(number.parse()?),
+            "address" => ScriptArg::Address(parse_address(value)?),
+            "vector<u8>" => ScriptArg::VectorU8(
+                if let Some(bytes) = value
+                    .strip_prefix("b\"")
+                    .and_then(|bytes| bytes.strip_suffix('"'))
+                {
+                    parse_byte_string(bytes)?
+                } else if value.contains('[') {
+                    parse_vec(value, "u8")?
+                } else {
+                    let bytes = value
+                        .strip_prefix("x\"")
+                        .and_then(|bytes| bytes.strip_suffix('"'))
+                        .unwrap_or(value);
+                    hex::decode(bytes)?
+                },
+            ),
+            "vector<u64>" => ScriptArg::VectorU64(parse_vec(value, "u64")?),
+            "vector<u128>" => ScriptArg::VectorU128(parse_vec(value, "u128")?),
+            "vector<bool>" => ScriptArg::VectorBool(parse_vec(value, "bool")?),
+            "vector<address>" => ScriptArg::VectorAddress(
+                parse_vec::<HexAddress>(&replace_addresses(value), "address")?
+                    .into_iter()
+                    .map(|addr| addr.0)
+                    .collect(),
+            ),
+            _ => return Err(anyhow!("Unexpected script parameter: {}", tp)),
+        })
+    }
+}
+
+/// Splits the script arguments by whitespaces outside of vectors and strings,
+/// e.g. `10 [1, 2] b"a b"` is split to `10`, `[1, 2]` and `b"a b"`.
+pub fn split_args(args: &str) -> Result<Vec<String>> {
+    let mut result = vec![];
+    let mut arg = String::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut chars = args.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                arg.push(c);
+                if let Some(escaped) = chars.next() {
+                    arg.push(escaped);
+                }
+                continue;
+            }
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| anyhow!("Unexpected ']' in arguments: {}", args))?;
+            }
+            c if c.is_whitespace() && !in_string && depth == 0 => {
+                if !arg.is_empty() {
+                    result.push(std::mem::take(&mut arg));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        arg.push(c);
+    }
+
+    if in_string {
+        return Err(anyhow!("Unterminated string in arguments: {}", args));
+    }
+    if depth > 0 {
+        return Err(anyhow!("Unclosed '[' in arguments: {}", args));
+    }
+    if !arg.is_empty() {
+        result.push(arg);
+    }
+    Ok(result)
+}
+
+/// Parses the content of the `b"..."` byte string.
+/// Supports the `\n`, `\r`, `\t`, `\\`, `\0`, `\"` and `\xHH` escapes.
+fn parse_byte_string(string: &str) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let escaped = chars
+            .next()
+            .ok_or_else(|| anyhow!("Invalid escape in byte string: {}", string))?;
+        bytes.push(match escaped {
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            '\\' => b'\\',
+            '0' => b'\0',
+            '"' => b'"',
+            'x' => {
+                let hex = chars.by_ref().take(2).collect::<String>();
+                u8::from_str_radix(&hex, 16)
+                    .map_err(|_| anyhow!("Invalid escape in byte string: {}", string))?
+            }
+            _ => return Err(anyhow!("Invalid escape in byte string: {}", string)),
+        });
+    }
+    Ok(bytes)
+}
+
+/// Parses the hex, ss58 or bech32 address.
+pub fn parse_address(addr: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(&replace_addresses(addr.trim()))
+        .map_err(|_| anyhow!("Invalid address: {}", addr))
+}
+
+struct HexAddress(AccountAddress);
+
+impl FromStr for HexAddress {
+    type Err = Error;
+
+    fn from_str(addr: &str) -> Result<Self, Self::Err> {
+        Ok(HexAddress(AccountAddress::from_hex_literal(addr)?))
+    }
+}
+
+/// Parses the vector in the `[n1, n2, ..., nn]` format.
+pub fn parse_vec<E>(tkn: &str, tp_name: &str) -> Result<Vec<E>, Error>
+where
+    E: FromStr,
+{
+    let map_err = |err| Error::msg(format!("{:?}", err));
+
+    let mut lexer = Lexer::new(tkn, "vec", Default::default());
+    lexer.advance().map_err(map_err)?;
+
+    if lexer.peek() != Tok::LBracket {
+        return Err(anyhow!("Vector in format  [n1, n2, ..., nn] is expected."));
+    }
+    lexer.advance().map_err(map_err)?;
+
+    let mut elements = vec![];
+    while lexer.peek() != Tok::RBracket {
+        match lexer.peek() {
+            Tok::Comma => {
+                lexer.advance().map_err(map_err)?;
+                continue;
+            }
+            Tok::EOF => {
+                return Err(anyhow!("unexpected end of vector."));
+            }
+            _ => {
+                elements.push(E::from_str(lexer.content()).map_err(|_| {
+                    anyhow!(
+                        "Failed to parse vector element. {} type is expected. Actual:'{}'",
+                        tp_name,
+                        lexer.content()
+                    )
+                })?);
+                lexer.advance().map_err(map_err)?;
+            }
+        }
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod test {
+    use diem::move_core_types::identifier::Identifier;
+    use diem::move_core_types::language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS};
+
+    use diem::move_core_types::account_address::AccountAddress;
+
+    use crate::script_args::{parse_type_param, replace_addresses, split_args, ScriptArg};
+
+    #[test]
+    fn test_parse_type_param() {
+        assert_eq!(parse_type_param("u8").unwrap(), TypeTag::U8);
+        assert_eq!(
+            parse_type_param("vector<u64>").unwrap(),
+            TypeTag::Vector(Box::new(TypeTag::U64))
+        );
+        assert_eq!(
+            parse_type_param("0x1::Coins::Balance<0x1::Coins::USD>").unwrap(),
+            TypeTag::Struct(StructTag {
+                address: CORE_CODE_ADDRESS,
+                module: Identifier::new("Coins").unwrap(),
+                name: Identifier::new("Balance").unwrap(),
+                type_params: vec![TypeTag::Struct(StructTag {
+                    address: CORE_CODE_ADDRESS,
+                    module: Identifier::new("Coins").unwrap(),
+                    name: Identifier::new("USD").unwrap(),
+                    type_params: vec![],
+                })],
+            })
+        );
+        assert!(parse_type_param("Coins::USD").is_err());
+        assert!(parse_type_param("u8 u64").is_err());
+    }
+
+    #[test]
+    fn test_replace_addresses() {
+        assert_eq!(
+            replace_addresses("1exaAg2VJRQbyUBAeXcktChCAqjVP9TUxF3zo23R2T6EGdE::Coins::USD"),
+            "0x1CF326C5AAA5AF9F0E2791E66310FE8F044FAADAF12567EAA0976959D1F7731F::Coins::USD"
+        );
+        assert_eq!(
+            replace_addresses("vector<0x1::Coins::USD>"),
+            "vector<0x1::Coins::USD>"
+        );
+        assert_eq!(
+            replace_addresses("wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh::M::T"),
+            "0xDE5F86CE8AD7944F272D693CB4625A955B610150::M::T"
+        );
+    }
+
+    #[test]
+    fn test_parse_script_args() {
+        assert_eq!(ScriptArg::parse("u8", "10").unwrap(), ScriptArg::U8(10));
+        assert_eq!(
+            ScriptArg::parse("u64", "10u64").unwrap(),
+            ScriptArg::U64(10)
+        );
+        assert_eq!(
+            ScriptArg::parse("u128", "340282366920938463463374607431768211455").unwrap(),
+            ScriptArg::U128(u128::MAX)
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<u8>", "68656c6c6f").unwrap(),
+            ScriptArg::VectorU8(b"hello".to_vec())
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<u8>", "x\"68656c6c6f\"").unwrap(),
+            ScriptArg::VectorU8(b"hello".to_vec())
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<u8>", "b\"hello\"").unwrap(),
+            ScriptArg::VectorU8(b"hello".to_vec())
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<u8>", r#"b"a \"b\"\n\x01""#).unwrap(),
+            ScriptArg::VectorU8(b"a \"b\"\n\x01".to_vec())
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<u8>", "[1, 2]").unwrap(),
+            ScriptArg::VectorU8(vec![1, 2])
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<u64>", "[10, 23]").unwrap(),
+            ScriptArg::VectorU64(vec![10, 23])
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<u128>", "[]").unwrap(),
+            ScriptArg::VectorU128(vec![])
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<bool>", "[true, false]").unwrap(),
+            ScriptArg::VectorBool(vec![true, false])
+        );
+        assert_eq!(
+            ScriptArg::parse("vector<address>", "[0x1, 0x2]").unwrap(),
+            ScriptArg::VectorAddress(vec![
+                AccountAddress::from_hex_literal("0x1").unwrap(),
+                AccountAddress::from_hex_literal("0x2").unwrap(),
+            ])
+        );
+        assert_eq!(
+            ScriptArg::parse("address", "0x1").unwrap(),
+            ScriptArg::Address(AccountAddress::from_hex_literal("0x1").unwrap())
+        );
+        assert!(ScriptArg::parse("u8", "256").is_err());
+        assert!(ScriptArg::parse("vector<u64>", "[1, true]").is_err());
+        assert!(ScriptArg::parse("vector<vector<u8>>", "[]").is_err());
+        assert!(ScriptArg::parse("vector<u8>", r#"b"\q""#).is_err());
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args(r#" 10  [10, 20] b"a b" x"01" [] "#).unwrap(),
+            vec!["10", "[10, 20]", r#"b"a b""#, r#"x"01""#, "[]"]
+        );
+        assert_eq!(
+            split_args(r#"b"a \" ]" 1"#).unwrap(),
+            vec![r#"b"a \" ]""#, "1"]
+        );
+        assert!(split_args("").unwrap().is_empty());
+        assert!(split_args("[1, 2").is_err());
+        assert!(split_args("1]").is_err());
+        assert!(split_args(r#"b"a"#).is_err());
+    }
+}
@@ -1,16 +1,12 @@
 use std::str::FromStr;
 
-use anyhow::{Result, Error, bail, anyhow};
+use anyhow::{Result, Error, anyhow};
 
 use diem::move_ir_types::location::Loc;
-use diem::{
-    move_lang::parser::ast::{ModuleAccess_, ModuleIdent_, Type, Type_},
-    move_core_types::language_storage::StructTag,
-};
 use diem::move_lang::parser::lexer::{Lexer, Tok};
 use diem::move_lang::parser::syntax::{parse_num, parse_type};
-use diem::account::Identifier;
 use diem::prelude::*;
+pub use lang::script_args::unwrap_spanned_ty;
 
 #[derive(Debug)]
 pub struct TypeTagQuery {
@@ -41,80 +37,6 @@ impl TypeTagQuery {
     }
 }
 
-pub fn unwrap_spanned_ty(ty: Type) -> Result<TypeTag, Error> {
-    fn unwrap_spanned_ty_(ty: Type, this: Option<AccountAddress>) -> Result<TypeTag, Error> {
-        let st = match ty.value {
-            Type_::Apply(ma, mut ty_params) => {
-                match (ma.value, this) {
-                    // N
-                    (ModuleAccess_::Name(name), this) => match name.value.as_ref() {
-                        "bool" => TypeTag::Bool,
-                        "u8" => TypeTag::U8,
-                        "u64" => TypeTag::U64,
-                        "u128" => TypeTag::U128,
-                        "address" => TypeTag::Address,
-                        "signer" => TypeTag::Signer,
-                        "Vec" if ty_params.len() == 1 => TypeTag::Vector(
-                            unwrap_spanned_ty_(ty_params.pop().unwrap(), this)
-                                .unwrap()
-                                .into(),
-                        ),
-                        _ => bail!(
-                            "Could not parse input: type without struct name & module address"
-                        ),
-                    },
-                    // M.S
-                    (ModuleAccess_::ModuleAccess(_module, _struct_name), None) => {
-                        bail!("Could not parse input: type without module address");
-                    }
-                    // M.S + parent address
-                    (ModuleAccess_::ModuleAccess(name, struct_name), Some(this)) => {
-                        TypeTag::Struct(StructTag {
-                            address: this,
-                            module: Identifier::new(name.0.value)?,
-                            name: Identifier::new(struct_name.value)?,
-                            type_params: ty_params
-                                .into_iter()
-                                .map(|ty| unwrap_spanned_ty_(ty, Some(this)))
-                                .map(|res| match res {
-                                    Ok(st) => st,
-                                    Err(err) => panic!("{:?}", err),
-                                })
-                                .collect(),
-                        })
-                    }
-
-                    // OxADDR.M.S
-                    (ModuleAccess_::QualifiedModuleAccess(module_id, struct_name), _) => {
-                        let ModuleIdent_ { name, address } = module_id.0.value;
-                        let address = AccountAddress::new(address.to_u8());
-                        TypeTag::Struct(StructTag {
-                            address,
-                            module: Identifier::new(name.0.value)?,
-                            name: Identifier::new(struct_name.value)?,
-                            type_params: ty_params
-                                .into_iter()
-                                .map(|ty| unwrap_spanned_ty_(ty, Some(address)))
-                                .map(|res| match res {
-                                    Ok(st) => st,
-                                    Err(err) => panic!("{:?}", err),
-                                })
-                                .collect(),
-                        })
-                    }
-                }
-            }
-            _ => {
-                bail!("Could not parse input: unsupported type");
-            }
-        };
-
-        Ok(st)
-    }
-
-    unwrap_spanned_ty_(ty, None)
-}
-
 pub fn parse(s: &str) -> Result<TypeTagQuery, Error> {
     let map_err = |err: Vec<(Loc, String)>| {
         anyhow!("Query parsing error:\n\t{:}", {